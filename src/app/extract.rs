@@ -0,0 +1,247 @@
+use std::io::{self, Write};
+
+use crate::cli::{ArgKind, Cli};
+use crate::domain::entities::drach::{DrachContext, DrachNeighborPosition};
+use crate::domain::entities::mask::MaskStrategy;
+use crate::domain::entities::Sequence;
+use crate::domain::usecases::header_template::HeaderTemplate;
+use crate::domain::usecases::records::Provenance;
+use crate::domain::usecases::scan_drachs::ScanDrachs;
+use crate::domain::usecases::write_drach_neighbor::{
+    BasicWriteStrategy, JsonWriteStrategy, TableWriteStrategy, WriteDrachNeighbor, WriteStrategy,
+};
+use crate::domain::usecases::write_report::{ReportLang, WriteReport};
+use crate::utils::output::OutputStream;
+use crate::Result;
+
+use super::{
+    commit_output_file, create_output_dir, create_output_file, create_scan_drachs,
+    create_single_output, existing_policy, flank_lengths, load_motif, mask_strategy, masking_seed,
+    padding_policy, provenance, read_seqs,
+};
+
+/// Settings shared by every sequence of an extraction.
+struct Extraction<'a> {
+    format: &'a str,
+    scan_drachs: ScanDrachs<'a>,
+    positions: Vec<DrachNeighborPosition>,
+    mask: Box<dyn MaskStrategy>,
+    seed: u64,
+}
+
+pub fn run(cli: &Cli) -> Result {
+    let format = cli.try_arg(ArgKind::Format).unwrap_or("fasta");
+    let motif = load_motif(cli)?;
+    let extraction = Extraction {
+        format,
+        scan_drachs: create_scan_drachs(cli, &motif),
+        positions: neighbor_positions(cli, format),
+        mask: mask_strategy(cli)?,
+        seed: masking_seed(cli)?,
+    };
+
+    let mut report_stream = create_report_stream(cli)?;
+    let mut write_report = match report_stream.as_mut() {
+        Some(stream) => Some(create_report(cli, stream, &extraction)?),
+        None => None,
+    };
+
+    if cli.has_arg(ArgKind::Out) {
+        write_single_output(cli, &extraction, &mut write_report)?;
+    } else {
+        write_output_dir(cli, &extraction, &mut write_report)?;
+    }
+
+    if let Some(stream) = report_stream {
+        stream.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Write the neighbors of every sequence to the output given by `--out`.
+/// The report still covers every sequence when the output is skipped.
+fn write_single_output(
+    cli: &Cli,
+    extraction: &Extraction,
+    write_report: &mut Option<WriteReport>,
+) -> Result {
+    let mut out = match create_single_output(cli)? {
+        Some(out) => out,
+        None => {
+            for seq in read_seqs(cli)? {
+                let seq = extraction.scan_drachs.prepare(seq?);
+                write_neighbors(None, write_report, &seq, extraction)?;
+            }
+
+            return Ok(());
+        }
+    };
+
+    let mut write_drach_neighbor = create_writer(cli, &mut out, extraction)?;
+    write_drach_neighbor.begin()?;
+
+    for seq in read_seqs(cli)? {
+        let seq = extraction.scan_drachs.prepare(seq?);
+        write_neighbors(
+            Some(&mut write_drach_neighbor),
+            write_report,
+            &seq,
+            extraction,
+        )?;
+    }
+
+    write_drach_neighbor.end()?;
+    commit_output_file(out)
+}
+
+/// Write the neighbors of each sequence to its own file in `--out-dir`.
+/// Sequences whose file is skipped are still reported.
+fn write_output_dir(
+    cli: &Cli,
+    extraction: &Extraction,
+    write_report: &mut Option<WriteReport>,
+) -> Result {
+    let mut out_dir = create_output_dir(cli, extraction.format)?;
+
+    for seq in read_seqs(cli)? {
+        let seq = extraction.scan_drachs.prepare(seq?);
+        let mut file = match create_output_file(cli, &mut out_dir, seq.id())? {
+            Some(file) => file,
+            None => {
+                write_neighbors(None, write_report, &seq, extraction)?;
+                continue;
+            }
+        };
+
+        let mut write_drach_neighbor = create_writer(cli, &mut file, extraction)?;
+
+        write_drach_neighbor.begin()?;
+        write_neighbors(
+            Some(&mut write_drach_neighbor),
+            write_report,
+            &seq,
+            extraction,
+        )?;
+        write_drach_neighbor.end()?;
+
+        commit_output_file(file)?;
+    }
+
+    Ok(())
+}
+
+/// Open the file given by `--report`, or stderr with `--verbose`.
+fn create_report_stream(cli: &Cli) -> Result<Option<OutputStream>> {
+    if cli.has_arg(ArgKind::Report) {
+        return OutputStream::open(cli.arg(ArgKind::Report), existing_policy(cli));
+    }
+
+    if cli.has_arg(ArgKind::Verbose) {
+        return Ok(Some(OutputStream::Stderr(io::stderr())));
+    }
+
+    Ok(None)
+}
+
+fn create_report<'a>(
+    cli: &Cli,
+    out: &'a mut dyn Write,
+    extraction: &Extraction,
+) -> Result<WriteReport<'a>> {
+    let (left_length, right_length) = flank_lengths(cli)?;
+    let lang = cli
+        .try_arg(ArgKind::Lang)
+        .and_then(ReportLang::from_code)
+        .unwrap_or_default();
+
+    Ok(WriteReport::new(
+        out,
+        lang,
+        left_length,
+        right_length,
+        padding_policy(cli),
+        extraction.seed,
+    ))
+}
+
+fn create_writer<'a>(
+    cli: &Cli,
+    out: &'a mut dyn Write,
+    extraction: &Extraction,
+) -> Result<WriteDrachNeighbor<'a>> {
+    let (left_length, right_length) = flank_lengths(cli)?;
+    let padding = padding_policy(cli);
+    let header = match cli.try_arg(ArgKind::Header) {
+        Some(template) => HeaderTemplate::parse(template)?,
+        None => HeaderTemplate::default(),
+    };
+    let provenance = provenance(cli, &extraction.scan_drachs, Some(extraction.seed))
+        .with_mask(extraction.mask.name());
+    let write_strategy = get_write_strategy(extraction.format, header, provenance);
+
+    Ok(WriteDrachNeighbor::new(
+        out,
+        write_strategy,
+        left_length,
+        right_length,
+        padding,
+        extraction.seed,
+    ))
+}
+
+/// Write the neighbors of every drach found in `seq` unless its output is
+/// skipped, and its section of the report.
+fn write_neighbors(
+    write_drach_neighbor: Option<&mut WriteDrachNeighbor>,
+    write_report: &mut Option<WriteReport>,
+    seq: &Sequence,
+    extraction: &Extraction,
+) -> Result {
+    let drachs = extraction.scan_drachs.scan(seq);
+    let ctx = DrachContext::new(seq, &drachs, extraction.scan_drachs.motif())
+        .with_mask(&*extraction.mask);
+
+    if let Some(write_report) = write_report {
+        write_report.write(&ctx)?;
+    }
+
+    let write_drach_neighbor = match write_drach_neighbor {
+        Some(write_drach_neighbor) => write_drach_neighbor,
+        None => return Ok(()),
+    };
+
+    for drach in drachs.iter() {
+        for position in extraction.positions.iter() {
+            write_drach_neighbor.write(drach, &ctx, *position)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tables hold both flanks of a drach in a single row, so they are written
+/// from its window.
+fn neighbor_positions(cli: &Cli, format: &str) -> Vec<DrachNeighborPosition> {
+    let is_table = matches!(format, "tsv" | "csv");
+
+    if is_table || cli.has_arg(ArgKind::Window) {
+        vec![DrachNeighborPosition::Window]
+    } else {
+        vec![DrachNeighborPosition::Left, DrachNeighborPosition::Right]
+    }
+}
+
+fn get_write_strategy(
+    format: &str,
+    header: HeaderTemplate,
+    provenance: Provenance,
+) -> Box<dyn WriteStrategy> {
+    match format {
+        "tsv" => Box::new(TableWriteStrategy::new('\t')),
+        "csv" => Box::new(TableWriteStrategy::new(',')),
+        "json" => Box::new(JsonWriteStrategy::new(false, provenance)),
+        "jsonl" => Box::new(JsonWriteStrategy::new(true, provenance)),
+        _ => Box::new(BasicWriteStrategy::new(header)),
+    }
+}
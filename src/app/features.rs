@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use crate::cli::{ArgKind, Cli};
+use crate::domain::entities::drach::DrachContext;
+use crate::domain::usecases::encode_features::{EncodeFeatures, SiteEncoder};
+use crate::domain::usecases::write_features::{
+    MatrixFormat, NpyMatrixFormat, TableMatrixFormat, WriteFeatures,
+};
+use crate::utils::npy;
+use crate::utils::output::OutputStream;
+use crate::Result;
+
+use super::{
+    commit_output_file, create_scan_drachs, create_single_output, existing_policy, flank_lengths,
+    load_motif, mask_strategy, masking_seed, padding_policy, read_seqs,
+};
+
+pub fn run(cli: &Cli) -> Result {
+    let format = cli.try_arg(ArgKind::Format).unwrap_or("tsv");
+    let motif = load_motif(cli)?;
+    let scan_drachs = create_scan_drachs(cli, &motif);
+    let encode = EncodeFeatures::parse(cli.try_arg(ArgKind::Encoding).unwrap_or("onehot"))?;
+    let mask = mask_strategy(cli)?;
+    let (left_length, right_length) = flank_lengths(cli)?;
+    let sites = SiteEncoder::new(
+        encode,
+        left_length,
+        right_length,
+        padding_policy(cli),
+        masking_seed(cli)?,
+    );
+    sites.check(&motif)?;
+
+    let mut index_stream = match format {
+        "npy" => {
+            match OutputStream::open(&index_path(cli.arg(ArgKind::Out)), existing_policy(cli))? {
+                Some(stream) => Some(stream),
+                None => return Ok(()),
+            }
+        }
+        _ => None,
+    };
+
+    let mut out = match create_single_output(cli)? {
+        Some(out) => out,
+        None => return Ok(()),
+    };
+
+    let matrix_format: Box<dyn MatrixFormat> = match index_stream.as_mut() {
+        Some(index) => Box::new(NpyMatrixFormat::new(index)),
+        None if format == "csv" => Box::new(TableMatrixFormat::new(',')),
+        None => Box::new(TableMatrixFormat::new('\t')),
+    };
+
+    let mut write_features = WriteFeatures::new(&mut out, matrix_format, sites);
+
+    write_features.begin(&motif)?;
+
+    for seq in read_seqs(cli)? {
+        let seq = scan_drachs.prepare(seq?);
+        let drachs = scan_drachs.scan(&seq);
+        let ctx = DrachContext::new(&seq, &drachs, &motif).with_mask(&*mask);
+
+        for drach in drachs.iter() {
+            write_features.write(drach, &ctx)?;
+        }
+    }
+
+    write_features.end()?;
+    let shape = write_features.shape();
+    drop(write_features);
+
+    if let Some(stream) = index_stream {
+        npy::patch_shape(&mut out, shape)?;
+        stream.commit()?;
+    }
+
+    out.flush()?;
+
+    commit_output_file(out)
+}
+
+/// Path of the index of the array written to `out`: `sites.npy` is indexed
+/// by `sites.index.tsv`.
+fn index_path(out: &str) -> String {
+    let stem = out.strip_suffix(".npy").unwrap_or(out);
+    format!("{}.index.tsv", stem)
+}
@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::cli::{ArgKind, Cli};
+use crate::domain::entities::drach::Strand;
+use crate::domain::entities::mask::MaskStrategy;
+use crate::domain::entities::Sequence;
+use crate::domain::usecases::scan_drachs::{ScanDrachs, StrandMode};
+use crate::utils::derive_seed;
+use crate::Result;
+
+use super::{
+    commit_output_file, create_output_dir, create_output_file, create_scan_drachs,
+    create_single_output, load_motif, mask_strategy, masking_seed, read_seqs,
+};
+
+pub fn run(cli: &Cli) -> Result {
+    let motif = load_motif(cli)?;
+    let scan_drachs = create_scan_drachs(cli, &motif);
+    let seed = masking_seed(cli)?;
+    let mask = mask_strategy(cli)?;
+
+    if cli.has_arg(ArgKind::Out) {
+        let mut out = match create_single_output(cli)? {
+            Some(out) => out,
+            None => return Ok(()),
+        };
+
+        for seq in read_seqs(cli)? {
+            let masked = mask_sequence(seq?, &scan_drachs, &*mask, seed)?;
+            writeln!(out, "{}", masked.to_fasta(80))?;
+        }
+
+        return commit_output_file(out);
+    }
+
+    let mut out_dir = create_output_dir(cli, "fasta")?;
+
+    for seq in read_seqs(cli)? {
+        let seq = seq?;
+        let mut file = match create_output_file(cli, &mut out_dir, seq.id())? {
+            Some(file) => file,
+            None => continue,
+        };
+
+        let masked = mask_sequence(seq, &scan_drachs, &*mask, seed)?;
+        writeln!(file, "{}", masked.to_fasta(80))?;
+        commit_output_file(file)?;
+    }
+
+    Ok(())
+}
+
+/// Copy of `seq` with every motif site masked by `mask`, keeping `seq` as its
+/// origin. The bases drawn only depend on `seed` and on the sequence id.
+fn mask_sequence(
+    seq: Sequence,
+    scan_drachs: &ScanDrachs,
+    mask: &dyn MaskStrategy,
+    seed: u64,
+) -> Result<Sequence> {
+    let seq = scan_drachs.prepare(seq);
+    let mut masked = Sequence::new(
+        seq.id(),
+        seq.header(),
+        seq.payload(),
+        Some(Box::new(seq.clone())),
+    );
+
+    let strands = match scan_drachs.strand_mode() {
+        StrandMode::Forward => vec![Strand::Forward],
+        StrandMode::Both => vec![Strand::Forward, Strand::Reverse],
+    };
+    let mut rng = StdRng::seed_from_u64(derive_seed(seed, seq.id()));
+
+    masked.mask_sites(
+        0..seq.payload().len(),
+        0..0,
+        &strands,
+        scan_drachs.motif(),
+        mask,
+        &mut rng,
+    )?;
+
+    Ok(masked)
+}
@@ -1,66 +1,188 @@
-use std::fs::{self, File, OpenOptions};
-
-use crate::cli::{ArgKind, Cli};
-use crate::domain::entities::{
-    drach::{DrachContext, DrachNeighborPosition},
-    Drach, Sequence,
-};
-use crate::domain::usecases::write_drach_neighbor::{
-    BasicWriteStrategy, VerboseWriteStrategy, WriteDrachNeighbor, WriteStrategy,
-};
-use crate::Result;
+use std::io::BufRead;
+
+use crate::cli::{ArgKind, Cli, Command, NAME, VERSION};
+use crate::domain::entities::drach::PaddingPolicy;
+use crate::domain::entities::mask::MaskStrategy;
+use crate::domain::entities::{sequence::FastaReader, Motif};
+use crate::domain::usecases::records::Provenance;
+use crate::domain::usecases::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+use crate::utils::compression::{CompressedWriter, Compression};
+use crate::utils::output::{ExistingPolicy, OutputDir, OutputStream, STDOUT_PATH};
+use crate::utils::sanitize_filename;
+use crate::{Error, Result};
+
+mod extract;
+mod features;
+mod mask;
+mod predict;
+mod scan;
+mod stats;
+mod train;
+
+pub const DEFAULT_FLANK_LENGTH: usize = 15;
 
 pub fn run(cli: Cli) -> Result {
-    let is_verbose = cli.has_arg(ArgKind::Verbose);
-    let seqs = load_seqs(&cli)?;
+    if cli.has_arg(ArgKind::Help) {
+        print!("{}", cli.usage());
+        return Ok(());
+    }
+
+    if cli.has_arg(ArgKind::Version) {
+        println!("{} {}", NAME, VERSION);
+        return Ok(());
+    }
+
+    match cli.command() {
+        Command::Extract => extract::run(&cli),
+        Command::Scan => scan::run(&cli),
+        Command::Stats => stats::run(&cli),
+        Command::Mask => mask::run(&cli),
+        Command::Features => features::run(&cli),
+        Command::Train => train::run(&cli),
+        Command::Predict => predict::run(&cli),
+    }
+}
+
+fn read_seqs(cli: &Cli) -> Result<FastaReader<Box<dyn BufRead>>> {
+    let path = cli.arg(ArgKind::Source);
+    let id_rule = cli.parse_arg(ArgKind::IdRule)?.unwrap_or_default();
+    Ok(FastaReader::open(path)?.with_id_rule(id_rule))
+}
+
+fn load_motif(cli: &Cli) -> Result<Motif> {
+    match cli.try_arg(ArgKind::Motif) {
+        Some(value) => Ok(Motif::parse(value)?),
+        None => Ok(Motif::default()),
+    }
+}
 
-    prepare_outdir(&cli)?;
+fn create_scan_drachs<'a>(cli: &Cli, motif: &'a Motif) -> ScanDrachs<'a> {
+    let mode = match cli.try_arg(ArgKind::ScanMode) {
+        Some("overlapping") => ScanMode::Overlapping,
+        Some("collapsed") => ScanMode::Collapsed,
+        _ => ScanMode::NonOverlapping,
+    };
 
-    for seq in seqs {
-        let drachs = Drach::from_sequence(&seq);
-        let ctx = DrachContext::new(&seq, &drachs);
+    let strand_mode = match cli.try_arg(ArgKind::Strand) {
+        Some("both") => StrandMode::Both,
+        _ => StrandMode::Forward,
+    };
 
-        let file = create_fasta_file(&cli, seq.id())?;
-        let write_strategy = get_write_strategy(is_verbose);
+    ScanDrachs::new(motif, mode, strand_mode)
+}
 
-        let mut write_drach_neighbor = WriteDrachNeighbor::new(&file, write_strategy);
+fn provenance(cli: &Cli, scan_drachs: &ScanDrachs, seed: Option<u64>) -> Provenance {
+    Provenance::new(cli.arg(ArgKind::Source), scan_drachs, seed)
+}
 
-        for drach in drachs.iter() {
-            write_drach_neighbor.write(drach, &ctx, DrachNeighborPosition::Left, 15)?;
-            write_drach_neighbor.write(drach, &ctx, DrachNeighborPosition::Right, 15)?;
+fn mask_strategy(cli: &Cli) -> Result<Box<dyn MaskStrategy>> {
+    let name = cli.try_arg(ArgKind::Mask).unwrap_or("random");
+
+    match crate::domain::entities::mask::mask_strategy(name) {
+        Some(strategy) => Ok(strategy),
+        None => {
+            let msg = format!("Unknown mask strategy '{}'", name);
+            Err(Box::new(Error::new(&msg)))
         }
     }
+}
 
-    Ok(())
+fn flank_lengths(cli: &Cli) -> Result<(usize, usize)> {
+    let length = cli
+        .parse_arg(ArgKind::Flank)?
+        .unwrap_or(DEFAULT_FLANK_LENGTH);
+    let left_length = cli.parse_arg(ArgKind::LeftFlank)?.unwrap_or(length);
+    let right_length = cli.parse_arg(ArgKind::RightFlank)?.unwrap_or(length);
+
+    Ok((left_length, right_length))
 }
 
-fn load_seqs(cli: &Cli) -> Result<Vec<Sequence>> {
-    let path = cli.arg(ArgKind::Source);
-    Sequence::load(path)
+fn padding_policy(cli: &Cli) -> PaddingPolicy {
+    let pad_char = if cli.has_arg(ArgKind::PadChar) {
+        cli.arg(ArgKind::PadChar).chars().next().unwrap()
+    } else {
+        'N'
+    };
+
+    if !cli.has_arg(ArgKind::Padding) {
+        return PaddingPolicy::Pad(pad_char);
+    }
+
+    match cli.arg(ArgKind::Padding) {
+        "skip" => PaddingPolicy::Skip,
+        "truncate" => PaddingPolicy::Truncate,
+        _ => PaddingPolicy::Pad(pad_char),
+    }
 }
 
-fn prepare_outdir(cli: &Cli) -> Result {
-    let path = cli.arg(ArgKind::OutDir);
-    fs::create_dir_all(path)?;
-    Ok(())
+/// Seed of the masking random generators. A random one is drawn when
+/// `--seed` is not given, and recorded like any other in the provenance.
+fn masking_seed(cli: &Cli) -> Result<u64> {
+    match cli.parse_arg(ArgKind::Seed)? {
+        Some(seed) => Ok(seed),
+        None => Ok(rand::random()),
+    }
 }
 
-fn create_fasta_file(cli: &Cli, filename: &str) -> Result<File> {
-    let path = format!("{}/{}.fasta", cli.arg(ArgKind::OutDir), filename);
-    fs::remove_file(&path)?;
+/// Create the output directory for files of the given `format`, which is
+/// also their extension.
+fn create_output_dir(cli: &Cli, format: &str) -> Result<OutputDir> {
+    let extension = format!(".{}{}", format, output_compression(cli).extension());
+    OutputDir::create(cli.arg(ArgKind::OutDir), &extension, existing_policy(cli))
+}
 
-    let file = OpenOptions::new()
-        .create_new(true)
-        .append(true)
-        .open(path)?;
+fn existing_policy(cli: &Cli) -> ExistingPolicy {
+    if cli.has_arg(ArgKind::Overwrite) {
+        ExistingPolicy::Overwrite
+    } else if cli.has_arg(ArgKind::SkipExisting) {
+        ExistingPolicy::Skip
+    } else {
+        ExistingPolicy::Fail
+    }
+}
 
-    Ok(file)
+type OutputFile = CompressedWriter<OutputStream>;
+
+/// Open the output file of the sequence `id`, or `None` if it is skipped.
+fn create_output_file(cli: &Cli, out_dir: &mut OutputDir, id: &str) -> Result<Option<OutputFile>> {
+    let file = match out_dir.open(&sanitize_filename(id))? {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+
+    let stream = OutputStream::File(file);
+    Ok(Some(CompressedWriter::new(stream, output_compression(cli))))
+}
+
+/// Open the file, or stdout, given by `--out` to receive the records of
+/// every sequence, defaulting to stdout. `None` is returned if the file is
+/// skipped.
+fn create_single_output(cli: &Cli) -> Result<Option<OutputFile>> {
+    let path = cli.try_arg(ArgKind::Out).unwrap_or(STDOUT_PATH);
+
+    let stream = match OutputStream::open(path, existing_policy(cli))? {
+        Some(stream) => stream,
+        None => return Ok(None),
+    };
+
+    let compression = match Compression::from_path(path) {
+        Compression::None => output_compression(cli),
+        compression => compression,
+    };
+
+    Ok(Some(CompressedWriter::new(stream, compression)))
+}
+
+/// Finish writing `file` and move it into place.
+fn commit_output_file(file: OutputFile) -> Result {
+    file.finish()?.commit()?;
+    Ok(())
 }
 
-fn get_write_strategy(is_verbose: bool) -> Box<dyn WriteStrategy> {
-    if let true = is_verbose {
-        Box::new(VerboseWriteStrategy)
+fn output_compression(cli: &Cli) -> Compression {
+    if cli.has_arg(ArgKind::Gzip) {
+        Compression::Gzip
     } else {
-        Box::new(BasicWriteStrategy)
+        Compression::None
     }
 }
@@ -0,0 +1,75 @@
+use std::io::Write;
+
+use crate::cli::{ArgKind, Cli};
+use crate::domain::entities::drach::DrachContext;
+use crate::domain::entities::mask;
+use crate::domain::usecases::model::ForestModel;
+use crate::domain::usecases::write_features::{MatrixFormat, TableMatrixFormat};
+use crate::utils::compression;
+use crate::{Error, Result};
+
+use super::{commit_output_file, create_scan_drachs, create_single_output, load_motif, read_seqs};
+
+pub fn run(cli: &Cli) -> Result {
+    let model = ForestModel::read(compression::open(cli.arg(ArgKind::Model))?)?;
+
+    if let Some(encoding) = cli.try_arg(ArgKind::Encoding) {
+        model.check_encoding(encoding)?;
+    }
+
+    let flank = cli.parse_arg(ArgKind::Flank)?;
+    model.check_flanks(
+        cli.parse_arg(ArgKind::LeftFlank)?.or(flank),
+        cli.parse_arg(ArgKind::RightFlank)?.or(flank),
+    )?;
+
+    let motif = match cli.try_arg(ArgKind::Motif) {
+        Some(_) => {
+            let motif = load_motif(cli)?;
+            model.check_motif(&motif)?;
+            motif
+        }
+        None => model.motif()?,
+    };
+
+    let scan_drachs = create_scan_drachs(cli, &motif);
+    let sites = model.site_encoder(cli.parse_arg(ArgKind::Seed)?)?;
+    let mask_name = &model.encoding().mask;
+
+    let mask = match mask::mask_strategy(mask_name) {
+        Some(mask) => mask,
+        None => {
+            let msg = format!("Unknown mask strategy '{}' in the model", mask_name);
+            return Err(Box::new(Error::new(&msg)));
+        }
+    };
+
+    let mut format = match cli.try_arg(ArgKind::Format) {
+        Some("csv") => TableMatrixFormat::new(','),
+        _ => TableMatrixFormat::new('\t'),
+    };
+
+    let mut out = match create_single_output(cli)? {
+        Some(out) => out,
+        None => return Ok(()),
+    };
+
+    format.begin(&mut out, &[String::from("probability")])?;
+
+    for seq in read_seqs(cli)? {
+        let seq = scan_drachs.prepare(seq?);
+        let drachs = scan_drachs.scan(&seq);
+        let ctx = DrachContext::new(&seq, &drachs, &motif).with_mask(&*mask);
+
+        for drach in drachs.iter() {
+            if let Some((neighbor, values)) = sites.encode(drach, &ctx)? {
+                let probability = model.forest().predict(&values);
+                format.write(&mut out, &neighbor, &[probability])?;
+            }
+        }
+    }
+
+    format.end(&mut out)?;
+    out.flush()?;
+    commit_output_file(out)
+}
@@ -0,0 +1,48 @@
+use std::io::{self, Write};
+
+use crate::cli::{ArgKind, Cli};
+use crate::domain::usecases::scan_drachs::ScanDrachs;
+use crate::domain::usecases::write_sites::{
+    BedSiteFormat, Gff3SiteFormat, JsonSiteFormat, SiteFormat, TableSiteFormat, WriteSites,
+};
+use crate::Result;
+
+use super::{create_scan_drachs, load_motif, provenance, read_seqs};
+
+pub fn run(cli: &Cli) -> Result {
+    let motif = load_motif(cli)?;
+    let scan_drachs = create_scan_drachs(cli, &motif);
+    let format = get_site_format(cli, &scan_drachs);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut write_sites = WriteSites::new(&mut out, format);
+
+    write_sites.begin()?;
+
+    for seq in read_seqs(cli)? {
+        let seq = scan_drachs.prepare(seq?);
+        let drachs = scan_drachs.scan(&seq);
+        write_sites.write(&seq, &drachs)?;
+    }
+
+    write_sites.end()?;
+    out.flush()?;
+    Ok(())
+}
+
+fn get_site_format(cli: &Cli, scan_drachs: &ScanDrachs) -> Box<dyn SiteFormat> {
+    match cli.try_arg(ArgKind::Format) {
+        Some("csv") => Box::new(TableSiteFormat::new(',')),
+        Some("bed") => Box::new(BedSiteFormat),
+        Some("gff3") => Box::new(Gff3SiteFormat::new(scan_drachs.motif())),
+        Some("json") => Box::new(JsonSiteFormat::new(
+            false,
+            provenance(cli, scan_drachs, None),
+        )),
+        Some("jsonl") => Box::new(JsonSiteFormat::new(
+            true,
+            provenance(cli, scan_drachs, None),
+        )),
+        _ => Box::new(TableSiteFormat::new('\t')),
+    }
+}
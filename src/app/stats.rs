@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+
+use crate::cli::Cli;
+use crate::Result;
+
+use super::{create_scan_drachs, load_motif, read_seqs};
+
+pub fn run(cli: &Cli) -> Result {
+    let motif = load_motif(cli)?;
+    let scan_drachs = create_scan_drachs(cli, &motif);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let mut total_length = 0;
+    let mut total_motifs = 0;
+
+    writeln!(out, "id\tlength\tmotifs\tmotifs_per_kb")?;
+
+    for seq in read_seqs(cli)? {
+        let seq = scan_drachs.prepare(seq?);
+        let length = seq.payload().len();
+        let motifs = scan_drachs.scan(&seq).len();
+
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{:.3}",
+            seq.id(),
+            length,
+            motifs,
+            per_kb(motifs, length)
+        )?;
+
+        total_length += length;
+        total_motifs += motifs;
+    }
+
+    writeln!(
+        out,
+        "total\t{}\t{}\t{:.3}",
+        total_length,
+        total_motifs,
+        per_kb(total_motifs, total_length)
+    )?;
+
+    Ok(())
+}
+
+fn per_kb(motifs: usize, length: usize) -> f64 {
+    if length == 0 {
+        return 0.0;
+    }
+
+    motifs as f64 * 1000.0 / length as f64
+}
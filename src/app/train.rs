@@ -0,0 +1,69 @@
+use crate::cli::{ArgKind, Cli};
+use crate::domain::entities::drach::DrachContext;
+use crate::domain::entities::forest::ForestParams;
+use crate::domain::usecases::model::{EncodingSettings, ForestModel, TrainingInfo};
+use crate::domain::usecases::train_forest::{read_labels, TrainForest};
+use crate::utils::compression;
+use crate::Result;
+
+use super::{
+    commit_output_file, create_scan_drachs, create_single_output, flank_lengths, load_motif,
+    mask_strategy, masking_seed, padding_policy, read_seqs,
+};
+
+pub fn run(cli: &Cli) -> Result {
+    let motif = load_motif(cli)?;
+    let scan_drachs = create_scan_drachs(cli, &motif);
+    let mask = mask_strategy(cli)?;
+    let (left_length, right_length) = flank_lengths(cli)?;
+    let seed = masking_seed(cli)?;
+
+    let encoding = EncodingSettings {
+        names: String::from(cli.try_arg(ArgKind::Encoding).unwrap_or("onehot")),
+        left_length,
+        right_length,
+        padding: padding_policy(cli),
+        mask: String::from(mask.name()),
+    };
+    let sites = encoding.site_encoder(seed)?;
+    sites.check(&motif)?;
+
+    let mut out = match create_single_output(cli)? {
+        Some(out) => out,
+        None => return Ok(()),
+    };
+
+    let labels = read_labels(compression::open(cli.arg(ArgKind::Labels))?)?;
+    let mut train = TrainForest::new(sites, labels);
+
+    for seq in read_seqs(cli)? {
+        let seq = scan_drachs.prepare(seq?);
+        let drachs = scan_drachs.scan(&seq);
+        let ctx = DrachContext::new(&seq, &drachs, &motif).with_mask(&*mask);
+
+        train.add(&drachs, &ctx)?;
+    }
+
+    let defaults = ForestParams::default();
+    let params = ForestParams {
+        trees: cli.parse_arg(ArgKind::Trees)?.unwrap_or(defaults.trees),
+        max_depth: cli
+            .parse_arg(ArgKind::MaxDepth)?
+            .unwrap_or(defaults.max_depth),
+        seed,
+        ..defaults
+    };
+    let forest = train.train(&params)?;
+
+    let training = TrainingInfo::new(
+        cli.arg(ArgKind::Source),
+        cli.arg(ArgKind::Labels),
+        train.len(),
+        train.positives(),
+        params,
+    );
+    let model = ForestModel::new(&motif, encoding, training, forest);
+
+    model.write(&mut out)?;
+    commit_output_file(out)
+}
@@ -1,43 +1,440 @@
-use std::collections::HashMap;
 use std::env;
+use std::error;
+use std::str::FromStr;
 
 use once_cell::sync::OnceCell;
 
-use crate::Result;
+use crate::domain::entities::mask::MASK_STRATEGIES;
+use crate::{Error, Result};
 
 use self::strategies::{BasicCliBuilderStrategy, BasicExtractorStrategy, BasicValidatorStrategy};
 
 pub mod strategies;
 
-static OPTIONS_MAP: OnceCell<HashMap<&'static str, ArgKind>> = OnceCell::new();
+pub const NAME: &str = env!("CARGO_PKG_NAME");
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn get_options_map() -> &'static HashMap<&'static str, ArgKind> {
-    OPTIONS_MAP.get_or_init(|| {
-        let mut m = HashMap::new();
-        m.insert("--src", ArgKind::Source);
-        m.insert("--out-dir", ArgKind::OutDir);
-        m.insert("--verbose", ArgKind::Verbose);
-        m
+static OPTIONS: OnceCell<Vec<CliOption>> = OnceCell::new();
+
+fn get_options() -> &'static [CliOption] {
+    use Command::*;
+
+    OPTIONS.get_or_init(|| {
+        let all = &[Extract, Scan, Stats, Mask, Features, Train, Predict];
+
+        vec![
+            CliOption::new("--src", ArgKind::Source, ValueKind::Path, all)
+                .with_help("FASTA file to read sequences from, or - for stdin"),
+            CliOption::new("--id-rule", ArgKind::IdRule, ValueKind::Text, all).with_help(
+                "Id taken from each header: auto, first-word, full or field:N (default: auto)",
+            ),
+            CliOption::new("--motif", ArgKind::Motif, ValueKind::Text, all)
+                .with_help("Motif preset (DRACH, RRACH, GGACU) or IUPAC pattern"),
+            CliOption::new(
+                "--scan-mode",
+                ArgKind::ScanMode,
+                ValueKind::Choice(&["non-overlapping", "overlapping", "collapsed"]),
+                all,
+            )
+            .with_help("How sites sharing bases are reported (default: non-overlapping)"),
+            CliOption::new(
+                "--strand",
+                ArgKind::Strand,
+                ValueKind::Choice(&["forward", "both"]),
+                all,
+            )
+            .with_help("Scan the reverse complement too, normalising T to U (default: forward)"),
+            CliOption::new(
+                "--out-dir",
+                ArgKind::OutDir,
+                ValueKind::Path,
+                &[Extract, Mask],
+            )
+            .with_help("Directory where one file per sequence is written"),
+            CliOption::new(
+                "--out",
+                ArgKind::Out,
+                ValueKind::Path,
+                &[Extract, Mask, Features, Train, Predict],
+            )
+            .with_help("Single file, or - for stdout, receiving every sequence instead"),
+            CliOption::new(
+                "--gzip",
+                ArgKind::Gzip,
+                ValueKind::Flag,
+                &[Extract, Mask, Features],
+            )
+            .with_help("Write gzip compressed output files"),
+            CliOption::new(
+                "--overwrite",
+                ArgKind::Overwrite,
+                ValueKind::Flag,
+                &[Extract, Mask, Features, Train, Predict],
+            )
+            .with_help("Replace output files that already exist"),
+            CliOption::new(
+                "--skip-existing",
+                ArgKind::SkipExisting,
+                ValueKind::Flag,
+                &[Extract, Mask, Features, Train, Predict],
+            )
+            .with_help("Leave output files that already exist untouched"),
+            CliOption::new(
+                "--fail-if-exists",
+                ArgKind::FailIfExists,
+                ValueKind::Flag,
+                &[Extract, Mask, Features, Train, Predict],
+            )
+            .with_help("Stop if an output file already exists (default)"),
+            CliOption::new(
+                "--format",
+                ArgKind::Format,
+                ValueKind::Choice(&["fasta", "tsv", "csv", "json", "jsonl"]),
+                &[Extract],
+            )
+            .with_help("Write flanks as FASTA, JSON or one table row per site (default: fasta)"),
+            CliOption::new(
+                "--format",
+                ArgKind::Format,
+                ValueKind::Choice(&["tsv", "csv", "bed", "gff3", "json", "jsonl"]),
+                &[Scan],
+            )
+            .with_help("Write sites as a table, BED6, GFF3 or JSON (default: tsv)"),
+            CliOption::new(
+                "--format",
+                ArgKind::Format,
+                ValueKind::Choice(&["tsv", "csv", "npy"]),
+                &[Features],
+            )
+            .with_help(
+                "Write features as a table or an uncompressed NumPy array with a .index.tsv \
+                 (default: tsv)",
+            ),
+            CliOption::new(
+                "--format",
+                ArgKind::Format,
+                ValueKind::Choice(&["tsv", "csv"]),
+                &[Predict],
+            )
+            .with_help("Write the probability of every site as a table (default: tsv)"),
+            CliOption::new(
+                "--encoding",
+                ArgKind::Encoding,
+                ValueKind::Text,
+                &[Features, Train, Predict],
+            )
+            .with_help(
+                "Comma separated encodings of each window: onehot, binary, kmer[:K-K], \
+                 ksnpf[:G-G], ncp, eiip, enac[:W], pseknc[:K] (default: onehot)",
+            ),
+            CliOption::new("--labels", ArgKind::Labels, ValueKind::Path, &[Train]).with_help(
+                "Tab separated id, start, label (0 or 1) and optional strand (+ or -, \
+                 default: +) of the training sites",
+            ),
+            CliOption::new("--model", ArgKind::Model, ValueKind::Path, &[Predict])
+                .with_help("Model written by the train command"),
+            CliOption::new("--trees", ArgKind::Trees, ValueKind::Number, &[Train])
+                .with_help("Number of trees of the forest (default: 100)"),
+            CliOption::new(
+                "--max-depth",
+                ArgKind::MaxDepth,
+                ValueKind::Number,
+                &[Train],
+            )
+            .with_help("Depth past which tree nodes are not split (default: 16)"),
+            CliOption::new("--header", ArgKind::Header, ValueKind::Text, &[Extract]).with_help(
+                "FASTA header template using {id} {index} {start} {end} {strand} {side} \
+                 {motif} {masked} {masked_bases} {length}",
+            ),
+            CliOption::new("--report", ArgKind::Report, ValueKind::Path, &[Extract]).with_help(
+                "Write a human readable report of every site to a file, or - for stdout",
+            ),
+            CliOption::new("--verbose", ArgKind::Verbose, ValueKind::Flag, &[Extract])
+                .with_help("Write the report to stderr when --report is not given"),
+            CliOption::new(
+                "--lang",
+                ArgKind::Lang,
+                ValueKind::Choice(&["en", "pt"]),
+                &[Extract],
+            )
+            .with_help("Language of the report (default: en)"),
+            CliOption::new(
+                "--seed",
+                ArgKind::Seed,
+                ValueKind::Number,
+                &[Extract, Mask, Features, Train, Predict],
+            )
+            .with_help("Seed of the bases drawn to mask sites, for reproducible outputs"),
+            CliOption::new(
+                "--mask",
+                ArgKind::Mask,
+                ValueKind::Choice(&MASK_STRATEGIES),
+                &[Extract, Mask, Features, Train],
+            )
+            .with_help(
+                "How sites are masked: random, n, soft, shuffle, point or none (default: random)",
+            ),
+            CliOption::new(
+                "--flank",
+                ArgKind::Flank,
+                ValueKind::Number,
+                &[Extract, Features, Train, Predict],
+            )
+            .with_help("Length of both flanks (default: 15)"),
+            CliOption::new(
+                "--left-flank",
+                ArgKind::LeftFlank,
+                ValueKind::Number,
+                &[Extract, Features, Train, Predict],
+            )
+            .with_help("Length of the left flank, overrides --flank"),
+            CliOption::new(
+                "--right-flank",
+                ArgKind::RightFlank,
+                ValueKind::Number,
+                &[Extract, Features, Train, Predict],
+            )
+            .with_help("Length of the right flank, overrides --flank"),
+            CliOption::new("--window", ArgKind::Window, ValueKind::Flag, &[Extract])
+                .with_help("Emit left flank, motif and right flank as one record"),
+            CliOption::new(
+                "--padding",
+                ArgKind::Padding,
+                ValueKind::Choice(&["skip", "truncate", "pad"]),
+                &[Extract, Features, Train],
+            )
+            .with_help("Policy for flanks crossing a sequence end (default: pad)"),
+            CliOption::new(
+                "--pad-char",
+                ArgKind::PadChar,
+                ValueKind::Char,
+                &[Extract, Features, Train],
+            )
+            .with_help("Character used by the pad policy (default: N)"),
+            CliOption::new("--help", ArgKind::Help, ValueKind::Flag, all)
+                .with_alias("-h")
+                .with_help("Print this help message"),
+            CliOption::new("--version", ArgKind::Version, ValueKind::Flag, all)
+                .with_alias("-V")
+                .with_help("Print the version"),
+        ]
     })
 }
 
+/// Find the option matching `name`, either by its name or its alias. An
+/// option may be declared once per command, so the one available for
+/// `command` is preferred.
+fn find_option(name: &str, command: Command) -> Option<&'static CliOption> {
+    let mut options = get_options()
+        .iter()
+        .filter(|option| option.name == name || option.alias == Some(name));
+
+    let first = options.next()?;
+
+    if first.is_available_for(command) {
+        return Some(first);
+    }
+
+    options
+        .find(|option| option.is_available_for(command))
+        .or(Some(first))
+}
+
+fn find_option_by_kind(kind: ArgKind, command: Command) -> &'static CliOption {
+    get_options()
+        .iter()
+        .filter(|option| option.kind == kind)
+        .find(|option| option.is_available_for(command))
+        .unwrap()
+}
+
 pub trait ExtractorStrategy {
-    fn extract(&self, args: env::Args) -> Vec<CliArg>;
+    fn extract(&self, command: Command, args: &[String]) -> Result<Vec<CliArg>>;
 }
 
 pub trait ValidatorStrategy {
-    fn validate(&self, args: &Vec<CliArg>) -> Result;
+    fn validate(&self, command: Command, args: &[CliArg]) -> Result;
 }
 
 pub trait CliBuilderStrategy {
     fn build(&self, dependencies: CliDependencies) -> Result<Cli>;
 }
 
-#[derive(Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 pub enum ArgKind {
     Source,
+    IdRule,
+    Motif,
+    ScanMode,
+    Strand,
     OutDir,
+    Out,
+    Gzip,
+    Overwrite,
+    SkipExisting,
+    FailIfExists,
+    Format,
+    Encoding,
+    Labels,
+    Model,
+    Trees,
+    MaxDepth,
+    Header,
+    Report,
     Verbose,
+    Lang,
+    Seed,
+    Mask,
+    Flank,
+    LeftFlank,
+    RightFlank,
+    Window,
+    Padding,
+    PadChar,
+    Help,
+    Version,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Write the neighbors of every motif site.
+    Extract,
+    /// List the motif sites of every sequence.
+    Scan,
+    /// Summarise the motif sites of every sequence.
+    Stats,
+    /// Write every sequence with its motif sites masked.
+    Mask,
+    /// Encode the window around every motif site as model features.
+    Features,
+    /// Train a random forest on labelled motif sites.
+    Train,
+    /// Score every motif site with a trained model.
+    Predict,
+}
+
+impl Command {
+    pub const ALL: [Command; 7] = [
+        Command::Extract,
+        Command::Scan,
+        Command::Stats,
+        Command::Mask,
+        Command::Features,
+        Command::Train,
+        Command::Predict,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Extract => "extract",
+            Command::Scan => "scan",
+            Command::Stats => "stats",
+            Command::Mask => "mask",
+            Command::Features => "features",
+            Command::Train => "train",
+            Command::Predict => "predict",
+        }
+    }
+
+    pub fn about(&self) -> &'static str {
+        match self {
+            Command::Extract => "Write the neighbors of every motif site (default)",
+            Command::Scan => "List the motif sites of every sequence",
+            Command::Stats => "Summarise the motif sites of every sequence",
+            Command::Mask => "Write every sequence with its motif sites masked",
+            Command::Features => "Encode the window around every motif site as model features",
+            Command::Train => "Train a random forest on labelled motif sites",
+            Command::Predict => "Score every motif site with a trained model",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|command| command.name() == name)
+    }
+}
+
+/// Kind of value an option expects.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ValueKind {
+    Flag,
+    Path,
+    Text,
+    Number,
+    Char,
+    Choice(&'static [&'static str]),
+}
+
+impl ValueKind {
+    pub fn takes_value(&self) -> bool {
+        *self != ValueKind::Flag
+    }
+
+    fn placeholder(&self) -> String {
+        match self {
+            ValueKind::Flag => String::new(),
+            ValueKind::Path => String::from(" <path>"),
+            ValueKind::Text => String::from(" <text>"),
+            ValueKind::Number => String::from(" <n>"),
+            ValueKind::Char => String::from(" <char>"),
+            ValueKind::Choice(choices) => format!(" <{}>", choices.join("|")),
+        }
+    }
+}
+
+pub struct CliOption {
+    name: &'static str,
+    alias: Option<&'static str>,
+    kind: ArgKind,
+    value: ValueKind,
+    commands: &'static [Command],
+    help: &'static str,
+}
+
+impl CliOption {
+    pub fn new(
+        name: &'static str,
+        kind: ArgKind,
+        value: ValueKind,
+        commands: &'static [Command],
+    ) -> Self {
+        Self {
+            name,
+            alias: None,
+            kind,
+            value,
+            commands,
+            help: "",
+        }
+    }
+
+    fn with_alias(mut self, alias: &'static str) -> Self {
+        self.alias = Some(alias);
+        self
+    }
+
+    fn with_help(mut self, help: &'static str) -> Self {
+        self.help = help;
+        self
+    }
+
+    /// Get the cli option's name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the cli option's kind.
+    pub fn kind(&self) -> ArgKind {
+        self.kind
+    }
+
+    /// Get the kind of value the cli option expects.
+    pub fn value(&self) -> ValueKind {
+        self.value
+    }
+
+    pub fn is_available_for(&self, command: Command) -> bool {
+        self.commands.contains(&command)
+    }
 }
 
 pub struct CliArg {
@@ -64,44 +461,148 @@ impl CliArg {
 }
 
 pub struct Cli {
+    command: Command,
+    /// Whether the command was given explicitly or defaulted to extract.
+    has_command: bool,
     args: Vec<CliArg>,
 }
 
 impl Cli {
     fn new(
-        args: env::Args,
+        args: Vec<String>,
         extractor: Box<dyn ExtractorStrategy>,
         validator: Box<dyn ValidatorStrategy>,
     ) -> Result<Self> {
-        let mut args = args;
-        args.next();
+        let mut args = args.into_iter().skip(1).peekable();
+
+        let has_command = matches!(args.peek(), Some(arg) if !arg.starts_with('-'));
+
+        let command = match args.peek() {
+            Some(arg) if has_command => match Command::from_name(arg) {
+                Some(command) => {
+                    args.next();
+                    command
+                }
+                None => {
+                    let msg = format!("Unknown command '{}'", arg);
+                    return Err(Box::new(Error::new(&msg)));
+                }
+            },
+            _ => Command::Extract,
+        };
 
-        let args = extractor.extract(args);
-        validator.validate(&args)?;
+        let args: Vec<String> = args.collect();
+        let args = extractor.extract(command, &args)?;
+        validator.validate(command, &args)?;
 
-        Ok(Self { args })
+        Ok(Self {
+            command,
+            has_command,
+            args,
+        })
     }
 
     pub fn builder() -> CliBuilder {
         CliBuilder::default()
     }
 
+    /// Get the cli's command.
+    pub fn command(&self) -> Command {
+        self.command
+    }
+
     pub fn has_arg(&self, kind: ArgKind) -> bool {
         self.args.iter().any(|arg| arg.kind() == &kind)
     }
 
     pub fn arg(&self, kind: ArgKind) -> &str {
+        self.try_arg(kind).unwrap()
+    }
+
+    pub fn try_arg(&self, kind: ArgKind) -> Option<&str> {
         self.args
             .iter()
             .find(|arg| arg.kind() == &kind)
-            .unwrap()
-            .value()
+            .map(|arg| arg.value())
+    }
+
+    /// Parse the value of the arg of the given kind, if present.
+    pub fn parse_arg<T>(&self, kind: ArgKind) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: error::Error + 'static,
+    {
+        match self.try_arg(kind) {
+            Some(value) => Ok(Some(value.parse()?)),
+            None => Ok(None),
+        }
     }
 
     /// Get a reference to the cli's args.
     pub fn args(&self) -> &[CliArg] {
         self.args.as_ref()
     }
+
+    /// Usage text for the cli's command.
+    pub fn usage(&self) -> String {
+        usage(self.has_command.then_some(self.command))
+    }
+}
+
+/// Width of the column of option names in the usage text.
+const USAGE_COLUMN: usize = 36;
+
+/// Build the usage text for `command`, or the general usage when no
+/// command is given.
+pub fn usage(command: Option<Command>) -> String {
+    let mut text = format!("{} {}\n\n", NAME, VERSION);
+
+    let options: Vec<&CliOption> = match command {
+        Some(command) => {
+            text.push_str(&format!("{}\n\n", command.about()));
+            text.push_str(&format!("Usage: {} {} [options]\n\n", NAME, command.name()));
+
+            get_options()
+                .iter()
+                .filter(|option| option.is_available_for(command))
+                .collect()
+        }
+        None => {
+            text.push_str(&format!("Usage: {} [command] [options]\n\n", NAME));
+            text.push_str("Commands:\n");
+
+            for command in Command::ALL {
+                text.push_str(&format!("  {:<10}{}\n", command.name(), command.about()));
+            }
+
+            text.push('\n');
+
+            get_options()
+                .iter()
+                .filter(|option| option.commands.len() == Command::ALL.len())
+                .collect()
+        }
+    };
+
+    text.push_str("Options:\n");
+
+    for option in options {
+        let name = match option.alias {
+            Some(alias) => format!("{}, {}", alias, option.name),
+            None => format!("    {}", option.name),
+        };
+        let name = format!("{}{}", name, option.value.placeholder());
+
+        // Names too long for the column get their help on the next line.
+        if name.len() < USAGE_COLUMN {
+            text.push_str(&format!("  {:<2$}{}\n", name, option.help, USAGE_COLUMN));
+        } else {
+            let indent = " ".repeat(USAGE_COLUMN + 2);
+            text.push_str(&format!("  {}\n{}{}\n", name, indent, option.help));
+        }
+    }
+
+    text
 }
 
 pub struct CliBuilder {
@@ -114,6 +615,10 @@ impl CliBuilder {
         self.build_strategy.build(self.dependencies)
     }
 
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.dependencies.args = args;
+    }
+
     pub fn set_extractor(&mut self, extractor: Box<dyn ExtractorStrategy>) {
         self.dependencies.extractor = extractor;
     }
@@ -133,7 +638,7 @@ impl Default for CliBuilder {
 }
 
 pub struct CliDependencies {
-    pub args: env::Args,
+    pub args: Vec<String>,
     pub extractor: Box<dyn ExtractorStrategy>,
     pub validator: Box<dyn ValidatorStrategy>,
 }
@@ -141,9 +646,119 @@ pub struct CliDependencies {
 impl Default for CliDependencies {
     fn default() -> Self {
         Self {
-            args: env::args(),
+            args: env::args().collect(),
             extractor: Box::new(BasicExtractorStrategy),
             validator: Box::new(BasicValidatorStrategy),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    mod usage {
+        use crate::cli::{usage, Command};
+
+        #[test]
+        fn should_separate_every_option_from_its_help() {
+            let text = usage(Some(Command::Extract));
+            let mask = text
+                .lines()
+                .skip_while(|line| !line.contains("--mask <"))
+                .take(2)
+                .collect::<Vec<&str>>();
+
+            assert!(text.contains("\n      --window                        Emit left flank"));
+            assert!(mask[0].ends_with("point|none>"));
+            assert!(mask[1].starts_with(&format!("{}How sites are masked", " ".repeat(38))));
+        }
+    }
+
+    mod build {
+        use crate::cli::{ArgKind, Cli, Command};
+        use crate::Result;
+
+        fn build(args: &[&str]) -> Result<Cli> {
+            let mut builder = Cli::builder();
+            let mut all = vec![String::from("rustsramp")];
+            all.extend(args.iter().map(|arg| String::from(*arg)));
+            builder.set_args(all);
+            builder.build()
+        }
+
+        #[test]
+        fn should_default_to_extract_command() -> Result {
+            let cli = build(&["--src", "a.fasta", "--out-dir", "out"])?;
+
+            assert_eq!(cli.command(), Command::Extract);
+            assert_eq!(cli.arg(ArgKind::Source), "a.fasta");
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_accept_equals_syntax_and_typed_values() -> Result {
+            let cli = build(&["extract", "--src=a.fasta", "--out-dir=out", "--flank=20"])?;
+
+            assert_eq!(cli.arg(ArgKind::OutDir), "out");
+            assert_eq!(cli.parse_arg::<usize>(ArgKind::Flank)?, Some(20));
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_resolve_options_declared_per_command() {
+            let scan = build(&["scan", "--src", "a.fasta", "--format", "bed"]);
+            let extract = build(&["--src", "a.fasta", "--out-dir", "out", "--format", "bed"]);
+
+            assert!(scan.is_ok());
+            assert_eq!(
+                extract.err().unwrap().to_string(),
+                "--format expects one of: fasta, tsv, csv, json, jsonl"
+            );
+        }
+
+        #[test]
+        fn should_reject_unknown_duplicate_and_valueless_options() {
+            let unknown = build(&["scan", "--src", "a.fasta", "--sorc", "b"]);
+            let duplicate = build(&["scan", "--src", "a.fasta", "--src", "b.fasta"]);
+            let missing = build(&["scan", "--src"]);
+            let misplaced = build(&["scan", "--src", "a.fasta", "--window"]);
+
+            assert_eq!(
+                unknown.err().unwrap().to_string(),
+                "Unknown option '--sorc'"
+            );
+            assert_eq!(
+                duplicate.err().unwrap().to_string(),
+                "Duplicate option '--src'"
+            );
+            assert_eq!(
+                missing.err().unwrap().to_string(),
+                "Missing value for '--src'"
+            );
+            assert_eq!(
+                misplaced.err().unwrap().to_string(),
+                "Option '--window' is not available for 'scan'"
+            );
+        }
+
+        #[test]
+        fn should_reject_invalid_numbers_and_choices() {
+            let number = build(&["--src", "a", "--out-dir", "b", "--flank", "ten"]);
+            let choice = build(&["--src", "a", "--out-dir", "b", "--padding", "wrap"]);
+
+            assert!(number.is_err());
+            assert!(choice.is_err());
+        }
+
+        #[test]
+        fn should_not_require_options_when_asking_for_help() -> Result {
+            let cli = build(&["stats", "--help"])?;
+
+            assert_eq!(cli.command(), Command::Stats);
+            assert!(cli.has_arg(ArgKind::Help));
+
+            Ok(())
+        }
+    }
+}
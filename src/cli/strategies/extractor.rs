@@ -1,40 +1,65 @@
-use std::env;
-
-use crate::cli::{get_options_map, CliArg, ExtractorStrategy};
+use crate::cli::{find_option, CliArg, Command, ExtractorStrategy};
+use crate::{Error, Result};
 
 pub struct BasicExtractorStrategy;
 
 impl ExtractorStrategy for BasicExtractorStrategy {
-    fn extract(&self, args: env::Args) -> Vec<CliArg> {
-        let args: Vec<String> = args.map(|arg| arg).collect();
-        let mut args_pos = vec![];
-        let mut mapped_args = vec![];
-        let options_map = get_options_map();
-
-        for (pos, arg) in args.iter().enumerate() {
-            if options_map.iter().any(|(key, _)| key == &arg) && !mapped_args.contains(&arg) {
-                args_pos.push(pos);
-                mapped_args.push(arg);
+    fn extract(&self, command: Command, args: &[String]) -> Result<Vec<CliArg>> {
+        let mut args = args.iter();
+        let mut mapped_args: Vec<CliArg> = vec![];
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                let msg = format!("Unexpected argument '{}'", arg);
+                return Err(Box::new(Error::new(&msg)));
             }
-        }
 
-        let args: Vec<CliArg> = args_pos
-            .into_iter()
-            .map(|pos| {
-                let (_, &kind) = options_map
-                    .iter()
-                    .find(|(key, _)| key == &&args.get(pos).unwrap())
-                    .unwrap();
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg.as_str(), None),
+            };
 
-                let value = match args.get(pos + 1) {
-                    Some(val) => val,
-                    None => "",
-                };
+            let option = match find_option(name, command) {
+                Some(option) => option,
+                None => {
+                    let msg = format!("Unknown option '{}'", name);
+                    return Err(Box::new(Error::new(&msg)));
+                }
+            };
+
+            if !option.is_available_for(command) {
+                let msg = format!(
+                    "Option '{}' is not available for '{}'",
+                    option.name(),
+                    command.name()
+                );
+                return Err(Box::new(Error::new(&msg)));
+            }
 
-                CliArg::new(kind, value)
-            })
-            .collect();
+            if mapped_args.iter().any(|arg| arg.kind() == &option.kind()) {
+                let msg = format!("Duplicate option '{}'", option.name());
+                return Err(Box::new(Error::new(&msg)));
+            }
+
+            let value = match (option.value().takes_value(), inline_value) {
+                (true, Some(value)) => value,
+                (true, None) => match args.next() {
+                    Some(value) if !value.starts_with("--") => value.as_str(),
+                    _ => {
+                        let msg = format!("Missing value for '{}'", option.name());
+                        return Err(Box::new(Error::new(&msg)));
+                    }
+                },
+                (false, Some(_)) => {
+                    let msg = format!("Option '{}' does not take a value", option.name());
+                    return Err(Box::new(Error::new(&msg)));
+                }
+                (false, None) => "",
+            };
+
+            mapped_args.push(CliArg::new(option.kind(), value));
+        }
 
-        args
+        Ok(mapped_args)
     }
 }
@@ -1,16 +1,123 @@
-use crate::cli::{ArgKind, CliArg, ValidatorStrategy};
+use crate::cli::{find_option_by_kind, ArgKind, CliArg, Command, ValidatorStrategy, ValueKind};
+use crate::utils::compression::GZIP_EXT;
+use crate::utils::output::STDOUT_PATH;
 use crate::{Error, Result};
 
 pub struct BasicValidatorStrategy;
 
 impl ValidatorStrategy for BasicValidatorStrategy {
-    fn validate(&self, args: &Vec<CliArg>) -> Result {
-        if !args.iter().any(|arg| arg.kind() == &ArgKind::Source) {
+    fn validate(&self, command: Command, args: &[CliArg]) -> Result {
+        let has_arg = |kind| args.iter().any(|arg| arg.kind() == &kind);
+
+        if has_arg(ArgKind::Help) || has_arg(ArgKind::Version) {
+            return Ok(());
+        }
+
+        if !has_arg(ArgKind::Source) {
             return Err(Box::new(Error::new("Missing --src argument")));
         }
 
-        if !args.iter().any(|arg| arg.kind() == &ArgKind::OutDir) {
-            return Err(Box::new(Error::new("Missing --out-dir argument")));
+        let needs_out_dir = matches!(command, Command::Extract | Command::Mask);
+
+        if needs_out_dir && !has_arg(ArgKind::OutDir) && !has_arg(ArgKind::Out) {
+            return Err(Box::new(Error::new("Missing --out-dir or --out argument")));
+        }
+
+        if has_arg(ArgKind::OutDir) && has_arg(ArgKind::Out) {
+            let msg = "Only one of --out-dir and --out can be used";
+            return Err(Box::new(Error::new(msg)));
+        }
+
+        let arg = |kind| {
+            args.iter()
+                .find(|arg| arg.kind() == &kind)
+                .map(|arg| arg.value())
+        };
+
+        if command == Command::Train && !has_arg(ArgKind::Labels) {
+            return Err(Box::new(Error::new("Missing --labels argument")));
+        }
+
+        if command == Command::Train && matches!(arg(ArgKind::Out), None | Some(STDOUT_PATH)) {
+            return Err(Box::new(Error::new("Missing --out file for the model")));
+        }
+
+        if command == Command::Predict && !has_arg(ArgKind::Model) {
+            return Err(Box::new(Error::new("Missing --model argument")));
+        }
+
+        if command == Command::Features && arg(ArgKind::Format) == Some("npy") {
+            if matches!(arg(ArgKind::Out), None | Some(STDOUT_PATH)) {
+                let msg = "--format npy needs an --out file";
+                return Err(Box::new(Error::new(msg)));
+            }
+
+            if has_arg(ArgKind::Gzip)
+                || arg(ArgKind::Out).is_some_and(|out| out.ends_with(GZIP_EXT))
+            {
+                let msg = "--format npy cannot be gzip compressed, since its header is \
+                           written last";
+                return Err(Box::new(Error::new(msg)));
+            }
+        }
+
+        if matches!(command, Command::Features | Command::Train)
+            && arg(ArgKind::Padding) == Some("truncate")
+        {
+            let msg = format!(
+                "--padding truncate cannot be used by the {} command",
+                command.name()
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        if matches!(
+            command,
+            Command::Features | Command::Train | Command::Predict
+        ) && arg(ArgKind::ScanMode) == Some("collapsed")
+        {
+            let msg = format!(
+                "--scan-mode collapsed cannot be used by the {} command, whose windows \
+                 all have the same length",
+                command.name()
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        let existing_policies = [
+            ArgKind::Overwrite,
+            ArgKind::SkipExisting,
+            ArgKind::FailIfExists,
+        ];
+
+        if existing_policies
+            .into_iter()
+            .filter(|kind| has_arg(*kind))
+            .count()
+            > 1
+        {
+            let msg = "Only one of --overwrite, --skip-existing and --fail-if-exists can be used";
+            return Err(Box::new(Error::new(msg)));
+        }
+
+        for arg in args.iter() {
+            let option = find_option_by_kind(*arg.kind(), command);
+            let value = arg.value();
+
+            let msg = match option.value() {
+                ValueKind::Number if value.parse::<usize>().is_err() => {
+                    format!("{} expects a non-negative integer", option.name())
+                }
+                ValueKind::Char if value.chars().count() != 1 => {
+                    format!("{} expects a single character", option.name())
+                }
+                ValueKind::Choice(choices) if !choices.contains(&value) => {
+                    format!("{} expects one of: {}", option.name(), choices.join(", "))
+                }
+                _ => continue,
+            };
+
+            return Err(Box::new(Error::new(&msg)));
         }
 
         Ok(())
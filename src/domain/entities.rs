@@ -1,5 +1,9 @@
 pub mod drach;
+pub mod forest;
+pub mod mask;
+pub mod motif;
 pub mod sequence;
 
 pub use drach::Drach;
+pub use motif::Motif;
 pub use sequence::Sequence;
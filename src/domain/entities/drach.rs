@@ -1,30 +1,73 @@
 use std::fmt::Display;
+use std::ops::Range;
 
-use regex::Regex;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+use crate::utils::derive_seed;
 use crate::{Error, Result};
 
-use super::Sequence;
+use super::mask::{MaskStrategy, RandomMask};
+use super::{Motif, Sequence};
+
+/// Strand a drach was found on. Reverse strand drachs keep the payload as
+/// read on the reverse complement, but their coordinates always refer to the
+/// forward strand.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strand {
+    #[default]
+    Forward,
+    Reverse,
+}
 
-pub const DRACH_RE: &'static str = r"([AGU][AG]AC[ACU])";
+impl Strand {
+    /// Symbol used for the strand in BED-like formats.
+    pub fn symbol(&self) -> char {
+        match self {
+            Strand::Forward => '+',
+            Strand::Reverse => '-',
+        }
+    }
+}
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Drach {
     payload: String,
+    #[serde(flatten)]
     position: DrachPosition,
+    strand: Strand,
 }
 
 impl Drach {
     #[must_use]
     pub fn new(payload: String, position: DrachPosition) -> Self {
-        Self { payload, position }
+        Self {
+            payload,
+            position,
+            strand: Strand::Forward,
+        }
+    }
+
+    #[must_use]
+    pub fn with_strand(payload: String, position: DrachPosition, strand: Strand) -> Self {
+        Self {
+            payload,
+            position,
+            strand,
+        }
     }
 
     pub fn from_sequence(sequence: &Sequence) -> Vec<Drach> {
-        let re = Regex::new(DRACH_RE).unwrap();
+        Self::from_motif(sequence, &Motif::default())
+    }
+
+    /// Find the non-overlapping occurrences of `motif` in the sequence.
+    pub fn from_motif(sequence: &Sequence, motif: &Motif) -> Vec<Drach> {
         let text = sequence.payload();
         let mut drachs = vec![];
 
-        for (index, a_match) in re.find_iter(text).enumerate() {
+        for (index, a_match) in motif.regex().find_iter(text).enumerate() {
             let payload = String::from(&text[a_match.range()]);
             let position = DrachPosition::new(index, a_match.start(), a_match.end());
             drachs.push(Drach::new(payload, position));
@@ -50,8 +93,15 @@ impl Drach {
     pub fn payload(&self) -> &str {
         self.payload.as_ref()
     }
+
+    /// Get the drach's strand.
+    #[must_use]
+    pub fn strand(&self) -> Strand {
+        self.strand
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DrachPosition {
     index: usize,
     start: usize,
@@ -85,10 +135,103 @@ impl DrachPosition {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DrachNeighborPosition {
     Left,
     Right,
+    /// Left flank, motif and right flank as a single contiguous record.
+    Window,
+}
+
+impl DrachNeighborPosition {
+    /// Name of the side of the drach the neighbor lies on.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DrachNeighborPosition::Left => "left",
+            DrachNeighborPosition::Right => "right",
+            DrachNeighborPosition::Window => "window",
+        }
+    }
+}
+
+/// What to do with neighbors that would cross one of the sequence ends.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaddingPolicy {
+    /// The neighbor is not emitted at all.
+    Skip,
+    /// The neighbor is cut at the sequence end, so it may be shorter than
+    /// requested.
+    Truncate,
+    /// The missing positions are filled with the given character.
+    Pad(char),
+}
+
+impl Default for PaddingPolicy {
+    fn default() -> Self {
+        Self::Pad('N')
+    }
+}
+
+/// A base of a neighbor that was changed to hide another site.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MaskedBase {
+    /// 0-based offset of the base in the emitted neighbor, padding included.
+    position: usize,
+    original: char,
+    replacement: char,
+}
+
+impl MaskedBase {
+    #[must_use]
+    pub fn new(position: usize, original: char, replacement: char) -> Self {
+        Self {
+            position,
+            original,
+            replacement,
+        }
+    }
+
+    /// Get the masked base's offset in the emitted neighbor.
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the masked base's original base.
+    #[must_use]
+    pub fn original(&self) -> char {
+        self.original
+    }
+
+    /// Get the base the masked base was replaced with.
+    #[must_use]
+    pub fn replacement(&self) -> char {
+        self.replacement
+    }
+
+    /// Write `bases` as a comma separated list of `position:original>replacement`,
+    /// or `-` when there are none.
+    #[must_use]
+    pub fn format_list(bases: &[MaskedBase]) -> String {
+        if bases.is_empty() {
+            return String::from("-");
+        }
+
+        let bases: Vec<String> = bases.iter().map(MaskedBase::to_string).collect();
+        bases.join(",")
+    }
+}
+
+impl Display for MaskedBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}>{}",
+            self.position, self.original, self.replacement
+        )
+    }
 }
 
 #[derive(Clone)]
@@ -96,7 +239,10 @@ pub struct DrachNeighbor<'a> {
     drach: &'a Drach,
     context: DrachContext<'a>,
     position: DrachNeighborPosition,
-    length: usize,
+    left_length: usize,
+    right_length: usize,
+    padding: PaddingPolicy,
+    seed: u64,
 }
 
 impl<'a> DrachNeighbor<'a> {
@@ -105,13 +251,19 @@ impl<'a> DrachNeighbor<'a> {
         drach: &'a Drach,
         context: DrachContext<'a>,
         position: DrachNeighborPosition,
-        length: usize,
+        left_length: usize,
+        right_length: usize,
+        padding: PaddingPolicy,
+        seed: u64,
     ) -> Self {
         Self {
             drach,
             context,
             position,
-            length,
+            left_length,
+            right_length,
+            padding,
+            seed,
         }
     }
 
@@ -137,70 +289,208 @@ impl<'a> DrachNeighbor<'a> {
         self.position
     }
 
-    /// Get the drach single neighbor's length.
+    /// Get the drach neighbor's left flank length.
+    #[must_use]
+    pub fn left_length(&self) -> usize {
+        self.left_length
+    }
+
+    /// Get the drach neighbor's right flank length.
+    #[must_use]
+    pub fn right_length(&self) -> usize {
+        self.right_length
+    }
+
+    /// Get the drach neighbor's padding policy.
+    #[must_use]
+    pub fn padding(&self) -> PaddingPolicy {
+        self.padding
+    }
+
+    /// Get the drach neighbor's masking seed.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get the requested length of the neighbor, which for a window also
+    /// includes the motif itself.
     #[must_use]
     pub fn length(&self) -> usize {
-        self.length
+        match self.position {
+            DrachNeighborPosition::Left => self.left_length,
+            DrachNeighborPosition::Right => self.right_length,
+            DrachNeighborPosition::Window => {
+                self.left_length + self.drach.payload().len() + self.right_length
+            }
+        }
     }
-}
 
-impl Display for DrachNeighbor<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Self {
-            drach,
-            context,
+    /// Get the length of the neighbor as it is emitted, after the padding
+    /// policy has been applied.
+    #[must_use]
+    pub fn actual_length(&self) -> usize {
+        match self.padding {
+            PaddingPolicy::Pad(_) => self.length(),
+            _ => self.range().len(),
+        }
+    }
+
+    /// Whether the requested neighbor crosses one of the sequence ends.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        let (missing_left, missing_right) = self.missing_lengths();
+        missing_left + missing_right > 0
+    }
+
+    /// Whether the padding policy discards this neighbor.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        self.padding == PaddingPolicy::Skip && self.is_truncated()
+    }
+
+    /// Same neighbor at another position around the drach.
+    #[must_use]
+    pub fn with_position(&self, position: DrachNeighborPosition) -> Self {
+        Self {
             position,
-            length,
-        } = self;
+            ..self.clone()
+        }
+    }
 
-        let start = match position {
-            DrachNeighborPosition::Left => drach.start() - length,
-            DrachNeighborPosition::Right => drach.end(),
+    /// Text of the neighbor, along with the bases that were changed to hide
+    /// other drachs lying inside it.
+    pub fn render(&self) -> Result<(String, Vec<MaskedBase>)> {
+        // Only the neighbor is copied and masked, since sites are looked
+        // for inside it alone.
+        let range = self.range();
+        let span = self.span(self.drach);
+        let keep = span.start.clamp(range.start, range.end) - range.start
+            ..span.end.clamp(range.start, range.end) - range.start;
+        let original = self
+            .context
+            .sequence
+            .region(range.clone(), self.drach.strand());
+        let mut sequence = original.clone();
+        let mut rng = self.rng();
+
+        sequence.mask_sites(
+            0..range.len(),
+            keep,
+            &[Strand::Forward],
+            self.context.motif,
+            self.context.mask,
+            &mut rng,
+        )?;
+
+        let (missing_left, missing_right) = match self.padding {
+            PaddingPolicy::Pad(_) => self.missing_lengths(),
+            _ => (0, 0),
         };
 
-        let end = match position {
-            DrachNeighborPosition::Left => drach.start(),
-            DrachNeighborPosition::Right => drach.end() + length,
+        let pad = match self.padding {
+            PaddingPolicy::Pad(c) => c,
+            _ => 'N',
         };
 
-        let overlaping_drachs: Vec<&Drach> = match position {
-            DrachNeighborPosition::Left => context
-                .drachs
-                .iter()
-                .filter(|d| d.end() > start && d.end() < drach.start())
-                .collect(),
-            DrachNeighborPosition::Right => context
-                .drachs
-                .iter()
-                .filter(|d| d.start() < end && d.start() > drach.end())
-                .collect(),
-        };
+        let masked = sequence
+            .payload()
+            .bytes()
+            .zip(original.payload().bytes())
+            .enumerate()
+            .filter(|(_, (base, original))| base != original)
+            .map(|(offset, (base, original))| {
+                MaskedBase::new(missing_left + offset, original as char, base as char)
+            })
+            .collect();
+
+        let mut text = String::with_capacity(self.actual_length());
+        text.extend(std::iter::repeat_n(pad, missing_left));
+        text.push_str(sequence.payload());
+        text.extend(std::iter::repeat_n(pad, missing_right));
+
+        Ok((text, masked))
+    }
 
-        let mut sequence = context.sequence.clone();
+    /// Random generator used to mask the neighbor. It only depends on the
+    /// seed and on where the neighbor lies, so a neighbor is always rendered
+    /// the same way.
+    fn rng(&self) -> StdRng {
+        let key = format!(
+            "{}|{}|{}|{}",
+            self.context.sequence.id(),
+            self.drach.start(),
+            self.drach.strand().symbol(),
+            self.position.name()
+        );
+
+        StdRng::seed_from_u64(derive_seed(self.seed, &key))
+    }
 
-        for drach in overlaping_drachs {
-            let range = drach.start() - 5..drach.end() + 5;
-            sequence.remove_drachs_from_range_mut(range);
+    /// Span of `drach` on the strand of the neighbor's drach, so flanks of
+    /// reverse strand drachs are read 5' to 3' on the reverse complement.
+    fn span(&self, drach: &Drach) -> Range<usize> {
+        match self.drach.strand() {
+            Strand::Forward => drach.start()..drach.end(),
+            Strand::Reverse => {
+                let length = self.context.sequence.payload().len();
+                length - drach.end()..length - drach.start()
+            }
         }
+    }
 
-        write!(
-            f,
-            "{}",
-            &sequence.payload()[sequence.clamp_range(start..end)]
-        )
+    /// Number of requested positions lying before the start and after the
+    /// end of the sequence.
+    fn missing_lengths(&self) -> (usize, usize) {
+        let Self {
+            left_length,
+            right_length,
+            position,
+            ..
+        } = self;
+
+        let span = self.span(self.drach);
+        let sequence_length = self.context.sequence.payload().len();
+        let missing_left = left_length.saturating_sub(span.start);
+        let missing_right = (span.end + right_length).saturating_sub(sequence_length);
+
+        match position {
+            DrachNeighborPosition::Left => (missing_left, 0),
+            DrachNeighborPosition::Right => (0, missing_right),
+            DrachNeighborPosition::Window => (missing_left, missing_right),
+        }
+    }
+
+    /// Range of the sequence covered by the neighbor, clamped to the
+    /// sequence bounds.
+    fn range(&self) -> Range<usize> {
+        let span = self.span(self.drach);
+        let start = span.start.saturating_sub(self.left_length);
+        let end = span.end + self.right_length;
+
+        let range = match self.position {
+            DrachNeighborPosition::Left => start..span.start,
+            DrachNeighborPosition::Right => span.end..end,
+            DrachNeighborPosition::Window => start..end,
+        };
+
+        self.context.sequence.clamp_range(range)
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct DrachNeighborBuilder<'a> {
     drach: Option<&'a Drach>,
     context: Option<DrachContext<'a>>,
     position: Option<DrachNeighborPosition>,
-    length: Option<usize>,
+    left_length: Option<usize>,
+    right_length: Option<usize>,
+    padding: Option<PaddingPolicy>,
+    seed: Option<u64>,
 }
 
 impl<'a> DrachNeighborBuilder<'a> {
-    pub fn build(&mut self) -> Result<DrachNeighbor, Error> {
+    pub fn build(&mut self) -> Result<DrachNeighbor<'a>, Error> {
         let drach = match self.drach.take() {
             Some(v) => v,
             None => return Err(Error::new("drach must be setted")),
@@ -216,16 +506,32 @@ impl<'a> DrachNeighborBuilder<'a> {
             None => return Err(Error::new("position must be setted")),
         };
 
-        let length = match self.length.take() {
+        let needs_left = position != DrachNeighborPosition::Right;
+        let needs_right = position != DrachNeighborPosition::Left;
+
+        let left_length = match self.left_length.take() {
             Some(v) => v,
-            None => return Err(Error::new("length must be setted")),
+            None if !needs_left => 0,
+            None => return Err(Error::new("left length must be setted")),
         };
 
+        let right_length = match self.right_length.take() {
+            Some(v) => v,
+            None if !needs_right => 0,
+            None => return Err(Error::new("right length must be setted")),
+        };
+
+        let padding = self.padding.take().unwrap_or_default();
+        let seed = self.seed.take().unwrap_or_default();
+
         Ok(DrachNeighbor {
             drach,
             context,
             position,
-            length,
+            left_length,
+            right_length,
+            padding,
+            seed,
         })
     }
 
@@ -247,34 +553,61 @@ impl<'a> DrachNeighborBuilder<'a> {
         self
     }
 
-    /// Set the drach neighbor builder's length.
+    /// Set both flank lengths of the drach neighbor builder.
     pub fn set_length(&mut self, length: usize) -> &mut Self {
-        self.length = Some(length);
+        self.left_length = Some(length);
+        self.right_length = Some(length);
         self
     }
-}
 
-impl Default for DrachNeighborBuilder<'_> {
-    fn default() -> Self {
-        Self {
-            drach: Default::default(),
-            context: Default::default(),
-            position: Default::default(),
-            length: Default::default(),
-        }
+    /// Set the drach neighbor builder's left flank length.
+    pub fn set_left_length(&mut self, length: usize) -> &mut Self {
+        self.left_length = Some(length);
+        self
+    }
+
+    /// Set the drach neighbor builder's right flank length.
+    pub fn set_right_length(&mut self, length: usize) -> &mut Self {
+        self.right_length = Some(length);
+        self
+    }
+
+    /// Set the drach neighbor builder's padding policy.
+    pub fn set_padding(&mut self, padding: PaddingPolicy) -> &mut Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Set the drach neighbor builder's masking seed.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
     }
 }
 
 #[derive(Clone)]
 pub struct DrachContext<'a> {
     sequence: &'a Sequence,
-    drachs: &'a Vec<Drach>,
+    drachs: &'a [Drach],
+    motif: &'a Motif,
+    mask: &'a dyn MaskStrategy,
 }
 
 impl<'a> DrachContext<'a> {
     #[must_use]
-    pub fn new(sequence: &'a Sequence, drachs: &'a Vec<Drach>) -> Self {
-        Self { sequence, drachs }
+    pub fn new(sequence: &'a Sequence, drachs: &'a [Drach], motif: &'a Motif) -> Self {
+        Self {
+            sequence,
+            drachs,
+            motif,
+            mask: &RandomMask,
+        }
+    }
+
+    /// Use `mask` to hide the drachs lying inside neighbors.
+    #[must_use]
+    pub fn with_mask(self, mask: &'a dyn MaskStrategy) -> Self {
+        Self { mask, ..self }
     }
 
     /// Get the drach context's sequence.
@@ -288,6 +621,18 @@ impl<'a> DrachContext<'a> {
     pub fn drachs(&self) -> &[Drach] {
         self.drachs
     }
+
+    /// Get the motif the drach context's drachs were scanned with.
+    #[must_use]
+    pub fn motif(&self) -> &Motif {
+        self.motif
+    }
+
+    /// Get the strategy used to mask the drach context's drachs.
+    #[must_use]
+    pub fn mask(&self) -> &dyn MaskStrategy {
+        self.mask
+    }
 }
 
 #[cfg(test)]
@@ -343,4 +688,244 @@ mod tests {
             }
         }
     }
+
+    mod drach_neighbor {
+        use crate::domain::entities::drach::{
+            DrachContext, DrachNeighbor, DrachNeighborPosition, PaddingPolicy,
+        };
+        use crate::domain::entities::{Drach, Motif, Sequence};
+
+        const PAYLOAD: &str = "CCCCCCCCCCGGACUUUUUUUUUUU";
+
+        fn render(position: DrachNeighborPosition, left: usize, right: usize) -> String {
+            let seq = Sequence::new("id", "header", PAYLOAD, None);
+            let drachs = Drach::from_sequence(&seq);
+            let motif = Motif::default();
+            let ctx = DrachContext::new(&seq, &drachs, &motif);
+
+            DrachNeighbor::builder()
+                .set_drach(&drachs[0])
+                .set_context(ctx)
+                .set_position(position)
+                .set_left_length(left)
+                .set_right_length(right)
+                .build()
+                .unwrap()
+                .render()
+                .unwrap()
+                .0
+        }
+
+        #[test]
+        fn flanks_should_respect_independent_lengths() {
+            assert_eq!(render(DrachNeighborPosition::Left, 3, 7), "CCC");
+            assert_eq!(render(DrachNeighborPosition::Right, 3, 7), "UUUUUUU");
+        }
+
+        #[test]
+        fn window_should_contain_both_flanks_and_motif() {
+            assert_eq!(render(DrachNeighborPosition::Window, 2, 4), "CCGGACUUUUU");
+        }
+
+        #[test]
+        fn builder_requires_only_the_lengths_the_position_uses() {
+            let seq = Sequence::new("id", "header", PAYLOAD, None);
+            let drachs = Drach::from_sequence(&seq);
+            let motif = Motif::default();
+            let ctx = DrachContext::new(&seq, &drachs, &motif);
+
+            let left = DrachNeighbor::builder()
+                .set_drach(&drachs[0])
+                .set_context(ctx.clone())
+                .set_position(DrachNeighborPosition::Left)
+                .set_left_length(5)
+                .build();
+
+            assert!(left.is_ok());
+
+            let window = DrachNeighbor::builder()
+                .set_drach(&drachs[0])
+                .set_context(ctx)
+                .set_position(DrachNeighborPosition::Window)
+                .set_left_length(5)
+                .build();
+
+            assert!(window.is_err());
+        }
+
+        mod padding {
+            use super::*;
+
+            const PAYLOAD: &str = "CCGGACUUU";
+
+            fn neighbor(
+                seq: &Sequence,
+                drachs: &[Drach],
+                position: DrachNeighborPosition,
+                padding: PaddingPolicy,
+            ) -> (String, usize, bool) {
+                let motif = Motif::default();
+                let ctx = DrachContext::new(seq, drachs, &motif);
+                let neighbor = DrachNeighbor::builder()
+                    .set_drach(&drachs[0])
+                    .set_context(ctx)
+                    .set_position(position)
+                    .set_length(5)
+                    .set_padding(padding)
+                    .build()
+                    .unwrap();
+
+                (
+                    neighbor.render().unwrap().0,
+                    neighbor.actual_length(),
+                    neighbor.is_skipped(),
+                )
+            }
+
+            #[test]
+            fn should_pad_flanks_crossing_sequence_ends() {
+                let seq = Sequence::new("id", "header", PAYLOAD, None);
+                let drachs = Drach::from_sequence(&seq);
+                let pad = PaddingPolicy::Pad('-');
+
+                let left = neighbor(&seq, &drachs, DrachNeighborPosition::Left, pad);
+                let right = neighbor(&seq, &drachs, DrachNeighborPosition::Right, pad);
+                let window = neighbor(&seq, &drachs, DrachNeighborPosition::Window, pad);
+
+                assert_eq!(left, (String::from("---CC"), 5, false));
+                assert_eq!(right, (String::from("UU---"), 5, false));
+                assert_eq!(window, (String::from("---CCGGACUUU---"), 15, false));
+            }
+
+            #[test]
+            fn should_truncate_and_report_real_length() {
+                let seq = Sequence::new("id", "header", PAYLOAD, None);
+                let drachs = Drach::from_sequence(&seq);
+                let truncate = PaddingPolicy::Truncate;
+
+                let left = neighbor(&seq, &drachs, DrachNeighborPosition::Left, truncate);
+                let window = neighbor(&seq, &drachs, DrachNeighborPosition::Window, truncate);
+
+                assert_eq!(left, (String::from("CC"), 2, false));
+                assert_eq!(window, (String::from("CCGGACUUU"), 9, false));
+            }
+
+            #[test]
+            fn should_skip_only_flanks_crossing_sequence_ends() {
+                let seq = Sequence::new("id", "header", "CCCCCGGACUUU", None);
+                let drachs = Drach::from_sequence(&seq);
+                let skip = PaddingPolicy::Skip;
+
+                let (_, _, left_skipped) =
+                    neighbor(&seq, &drachs, DrachNeighborPosition::Left, skip);
+                let (_, _, right_skipped) =
+                    neighbor(&seq, &drachs, DrachNeighborPosition::Right, skip);
+
+                assert!(!left_skipped);
+                assert!(right_skipped);
+            }
+        }
+
+        mod scan_modes {
+            use crate::domain::entities::drach::{
+                DrachContext, DrachNeighbor, DrachNeighborPosition,
+            };
+            use crate::domain::entities::Motif;
+            use crate::domain::entities::Sequence;
+            use crate::domain::usecases::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+
+            const PAYLOAD: &str = "UUUUUUCCGGACAGACUCCUUUUUUU";
+
+            fn windows(mode: ScanMode) -> Vec<String> {
+                let seq = Sequence::new("s", "s", PAYLOAD, None);
+                let motif = Motif::default();
+                let drachs = ScanDrachs::new(&motif, mode, StrandMode::Forward).scan(&seq);
+                let ctx = DrachContext::new(&seq, &drachs, &motif);
+
+                drachs
+                    .iter()
+                    .map(|drach| {
+                        DrachNeighbor::builder()
+                            .set_drach(drach)
+                            .set_context(ctx.clone())
+                            .set_position(DrachNeighborPosition::Window)
+                            .set_length(3)
+                            .build()
+                            .unwrap()
+                            .render()
+                            .unwrap()
+                            .0
+                    })
+                    .collect()
+            }
+
+            #[test]
+            fn should_keep_collapsed_sites_whole() {
+                assert_eq!(windows(ScanMode::Collapsed), ["UCCGGACAGACUCCU"]);
+            }
+
+            #[test]
+            fn should_keep_overlapping_sites_whole() {
+                let windows = windows(ScanMode::Overlapping);
+
+                assert_eq!(windows.len(), 2);
+                assert_eq!(&windows[0][3..8], "GGACA");
+                assert_eq!(&windows[1][3..8], "AGACU");
+            }
+        }
+
+        mod seed {
+            use super::*;
+            use crate::domain::entities::drach::MaskedBase;
+
+            const PAYLOAD: &str = "CCGGACUUUUUGGACUUUUUUUU";
+
+            fn render_with_seed(seed: u64) -> (String, Vec<MaskedBase>) {
+                let seq = Sequence::new("id", "header", PAYLOAD, None);
+                let drachs = Drach::from_sequence(&seq);
+                let motif = Motif::default();
+                let ctx = DrachContext::new(&seq, &drachs, &motif);
+
+                DrachNeighbor::builder()
+                    .set_drach(&drachs[0])
+                    .set_context(ctx)
+                    .set_position(DrachNeighborPosition::Right)
+                    .set_length(12)
+                    .set_seed(seed)
+                    .build()
+                    .unwrap()
+                    .render()
+                    .unwrap()
+            }
+
+            #[test]
+            fn should_mask_the_same_way_for_the_same_seed() {
+                let (text, masked) = render_with_seed(7);
+
+                assert_eq!(render_with_seed(7), (text.clone(), masked.clone()));
+                assert!(!masked.is_empty());
+                assert!(!text.contains("GGACU"));
+            }
+
+            #[test]
+            fn should_record_the_masked_bases() {
+                let (text, masked) = render_with_seed(7);
+                let original = &PAYLOAD[7..19];
+
+                for base in masked.iter() {
+                    let position = base.position();
+
+                    assert_eq!(base.original(), original.as_bytes()[position] as char);
+                    assert_eq!(base.replacement(), text.as_bytes()[position] as char);
+                    assert_ne!(base.original(), base.replacement());
+                }
+
+                let unchanged = (0..text.len())
+                    .filter(|&i| masked.iter().all(|base| base.position() != i))
+                    .all(|i| text.as_bytes()[i] == original.as_bytes()[i]);
+
+                assert!(unchanged);
+            }
+        }
+    }
 }
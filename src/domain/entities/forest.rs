@@ -0,0 +1,203 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::derive_seed;
+use crate::{Error, Result};
+
+pub use tree::DecisionTree;
+
+mod tree;
+
+/// Settings of a [`RandomForest`] training.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ForestParams {
+    /// Number of trees grown.
+    pub trees: usize,
+    /// Depth past which nodes are not split anymore.
+    pub max_depth: usize,
+    /// Fewest samples a node needs to be split.
+    pub min_samples_split: usize,
+    /// Seed of the bootstrap samples and of the features tried at each
+    /// split.
+    pub seed: u64,
+}
+
+impl Default for ForestParams {
+    fn default() -> Self {
+        Self {
+            trees: 100,
+            max_depth: 16,
+            min_samples_split: 2,
+            seed: 0,
+        }
+    }
+}
+
+/// A binary classifier averaging CART trees grown on bootstrap samples,
+/// each split choosing among the square root of the features.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomForest {
+    features: usize,
+    trees: Vec<DecisionTree>,
+}
+
+impl RandomForest {
+    /// Grow a forest predicting `labels` from the rows of `samples`.
+    pub fn train(samples: &[Vec<f64>], labels: &[bool], params: &ForestParams) -> Result<Self> {
+        if samples.is_empty() {
+            return Err(Box::new(Error::new(
+                "Cannot train a forest without samples",
+            )));
+        }
+
+        if params.trees == 0 {
+            return Err(Box::new(Error::new("A forest needs at least one tree")));
+        }
+
+        if samples.len() != labels.len() {
+            let msg = format!("Got {} samples but {} labels", samples.len(), labels.len());
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        let features = samples[0].len();
+
+        if features == 0 {
+            let msg = "Cannot train a forest on samples without features";
+            return Err(Box::new(Error::new(msg)));
+        }
+
+        if samples.iter().any(|sample| sample.len() != features) {
+            let msg = "Every sample must have the same number of features";
+            return Err(Box::new(Error::new(msg)));
+        }
+
+        let trees = (0..params.trees)
+            .map(|index| {
+                let seed = derive_seed(params.seed, &format!("tree{}", index));
+                let mut rng = StdRng::seed_from_u64(seed);
+                let bootstrap: Vec<usize> = (0..samples.len())
+                    .map(|_| rng.gen_range(0..samples.len()))
+                    .collect();
+
+                DecisionTree::grow(samples, labels, bootstrap, params, &mut rng)
+            })
+            .collect();
+
+        Ok(Self { features, trees })
+    }
+
+    /// Check that the trees can score samples of the forest's features,
+    /// such as after reading them from a file.
+    pub fn check(&self) -> Result {
+        if self.trees.is_empty() {
+            return Err(Box::new(Error::new("A forest needs at least one tree")));
+        }
+
+        for (number, tree) in self.trees.iter().enumerate() {
+            tree.check(number, self.features)?;
+        }
+
+        Ok(())
+    }
+
+    /// Number of features of the samples the forest was trained on.
+    pub fn features(&self) -> usize {
+        self.features
+    }
+
+    /// Get the forest's trees.
+    pub fn trees(&self) -> &[DecisionTree] {
+        &self.trees
+    }
+
+    /// Probability that `sample` belongs to the positive class, as the mean
+    /// of the trees' predictions.
+    pub fn predict(&self, sample: &[f64]) -> f64 {
+        if self.trees.is_empty() {
+            return 0.0;
+        }
+
+        let sum: f64 = self.trees.iter().map(|tree| tree.predict(sample)).sum();
+        sum / self.trees.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod random_forest {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        use crate::domain::entities::forest::{ForestParams, RandomForest};
+
+        /// Points labelled by whether they lie above the diagonal, with a
+        /// noise feature.
+        fn samples(n: usize, seed: u64) -> (Vec<Vec<f64>>, Vec<bool>) {
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            (0..n)
+                .map(|_| {
+                    let (x, y, noise) = (rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>());
+                    (vec![x, y, noise], y > x)
+                })
+                .unzip()
+        }
+
+        #[test]
+        fn should_learn_a_separable_problem() {
+            let (train, labels) = samples(300, 1);
+            let (test, expected) = samples(100, 2);
+            let params = ForestParams {
+                trees: 25,
+                seed: 7,
+                ..ForestParams::default()
+            };
+
+            let forest = RandomForest::train(&train, &labels, &params).unwrap();
+            let correct = test
+                .iter()
+                .zip(expected.iter())
+                .filter(|(sample, label)| (forest.predict(sample) > 0.5) == **label)
+                .count();
+
+            assert!(correct >= 90, "{} correct", correct);
+        }
+
+        #[test]
+        fn should_grow_the_same_forest_for_the_same_seed() {
+            let (train, labels) = samples(50, 1);
+            let params = ForestParams {
+                trees: 5,
+                ..ForestParams::default()
+            };
+
+            assert_eq!(
+                RandomForest::train(&train, &labels, &params).unwrap(),
+                RandomForest::train(&train, &labels, &params).unwrap()
+            );
+        }
+
+        #[test]
+        fn should_reject_ragged_samples() {
+            let samples = vec![vec![0.0, 1.0], vec![1.0]];
+            let res = RandomForest::train(&samples, &[true, false], &ForestParams::default());
+
+            assert_eq!(
+                res.err().unwrap().to_string(),
+                "Every sample must have the same number of features"
+            );
+        }
+
+        #[test]
+        fn should_reject_samples_without_features() {
+            let samples = vec![vec![], vec![]];
+            let res = RandomForest::train(&samples, &[true, false], &ForestParams::default());
+
+            assert_eq!(
+                res.err().unwrap().to_string(),
+                "Cannot train a forest on samples without features"
+            );
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use rand::seq::index::sample;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::ForestParams;
+use crate::{Error, Result};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Node {
+    /// Probability of the positive class among the samples reaching it.
+    Leaf { probability: f64 },
+    /// Samples whose `feature` is at most `threshold` go to `left`, the
+    /// others to `right`. Children are indices in the tree's nodes.
+    Split {
+        feature: usize,
+        threshold: f64,
+        left: usize,
+        right: usize,
+    },
+}
+
+/// A CART classification tree split on Gini impurity.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTree {
+    nodes: Vec<Node>,
+}
+
+/// Best split found for a node.
+struct Split {
+    feature: usize,
+    threshold: f64,
+    impurity: f64,
+}
+
+impl DecisionTree {
+    /// Grow a tree on the samples at `indices`, which may repeat.
+    pub(super) fn grow(
+        samples: &[Vec<f64>],
+        labels: &[bool],
+        indices: Vec<usize>,
+        params: &ForestParams,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let mut tree = Self { nodes: vec![] };
+        tree.grow_node(samples, labels, indices, 0, params, rng);
+        tree
+    }
+
+    /// Probability that `sample` belongs to the positive class.
+    pub fn predict(&self, sample: &[f64]) -> f64 {
+        let mut index = 0;
+
+        loop {
+            match &self.nodes[index] {
+                Node::Leaf { probability } => return *probability,
+                Node::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    let value = sample.get(*feature).copied().unwrap_or(0.0);
+                    index = if value <= *threshold { *left } else { *right };
+                }
+            }
+        }
+    }
+
+    /// Check that every node of the tree, numbered `number` in its forest,
+    /// can be reached from the root and splits on one of the `features`,
+    /// so trees read from a file cannot make [`DecisionTree::predict`]
+    /// panic or loop.
+    pub(super) fn check(&self, number: usize, features: usize) -> Result {
+        if self.nodes.is_empty() {
+            let msg = format!("Tree {} has no nodes", number);
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let msg = match node {
+                Node::Leaf { probability } if !(0.0..=1.0).contains(probability) => format!(
+                    "Leaf {} of tree {} has probability {}, expected 0 to 1",
+                    index, number, probability
+                ),
+                Node::Split { feature, .. } if *feature >= features => format!(
+                    "Node {} of tree {} splits on feature {} but there are {} features",
+                    index, number, feature, features
+                ),
+                Node::Split { left, right, .. } => {
+                    match [*left, *right]
+                        .into_iter()
+                        .find(|child| *child <= index || *child >= self.nodes.len())
+                    {
+                        Some(child) => format!(
+                            "Node {} of tree {} points to node {}, expected one after it among \
+                             the {} nodes",
+                            index,
+                            number,
+                            child,
+                            self.nodes.len()
+                        ),
+                        None => continue,
+                    }
+                }
+                Node::Leaf { .. } => continue,
+            };
+
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        Ok(())
+    }
+
+    /// Number of nodes of the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Grow the subtree of the samples at `indices` and return the index
+    /// of its root.
+    fn grow_node(
+        &mut self,
+        samples: &[Vec<f64>],
+        labels: &[bool],
+        indices: Vec<usize>,
+        depth: usize,
+        params: &ForestParams,
+        rng: &mut dyn RngCore,
+    ) -> usize {
+        let positives = indices.iter().filter(|&&i| labels[i]).count();
+        let probability = positives as f64 / indices.len() as f64;
+        let node = self.nodes.len();
+        self.nodes.push(Node::Leaf { probability });
+
+        let is_pure = positives == 0 || positives == indices.len();
+
+        if is_pure || depth >= params.max_depth || indices.len() < params.min_samples_split {
+            return node;
+        }
+
+        let split = match best_split(samples, labels, &indices, rng) {
+            Some(split) => split,
+            None => return node,
+        };
+
+        let (left, right): (Vec<usize>, Vec<usize>) = indices
+            .into_iter()
+            .partition(|&i| samples[i][split.feature] <= split.threshold);
+
+        let left = self.grow_node(samples, labels, left, depth + 1, params, rng);
+        let right = self.grow_node(samples, labels, right, depth + 1, params, rng);
+
+        self.nodes[node] = Node::Split {
+            feature: split.feature,
+            threshold: split.threshold,
+            left,
+            right,
+        };
+
+        node
+    }
+}
+
+/// Split lowering the Gini impurity the most, among the square root of the
+/// features drawn at random. `None` if no drawn feature separates the
+/// samples.
+fn best_split(
+    samples: &[Vec<f64>],
+    labels: &[bool],
+    indices: &[usize],
+    rng: &mut dyn RngCore,
+) -> Option<Split> {
+    let features = samples[indices[0]].len();
+    let tried = ((features as f64).sqrt() as usize).clamp(1, features.max(1));
+    let total = indices.len() as f64;
+    let positives = indices.iter().filter(|&&i| labels[i]).count() as f64;
+
+    let mut best: Option<Split> = None;
+    let mut sorted = indices.to_vec();
+
+    for feature in sample(rng, features, tried).into_iter() {
+        sorted.sort_by(|&a, &b| samples[a][feature].total_cmp(&samples[b][feature]));
+
+        let mut left_positives = 0.0;
+
+        for (position, pair) in sorted.windows(2).enumerate() {
+            if labels[pair[0]] {
+                left_positives += 1.0;
+            }
+
+            let (value, next) = (samples[pair[0]][feature], samples[pair[1]][feature]);
+
+            if value == next {
+                continue;
+            }
+
+            let left = (position + 1) as f64;
+            let right = total - left;
+            let impurity = (left * gini(left_positives, left)
+                + right * gini(positives - left_positives, right))
+                / total;
+
+            if best.as_ref().is_none_or(|best| impurity < best.impurity) {
+                best = Some(Split {
+                    feature,
+                    threshold: value + (next - value) / 2.0,
+                    impurity,
+                });
+            }
+        }
+    }
+
+    best
+}
+
+fn gini(positives: f64, total: f64) -> f64 {
+    let p = positives / total;
+    2.0 * p * (1.0 - p)
+}
+
+#[cfg(test)]
+mod tests {
+    mod decision_tree {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::domain::entities::forest::{DecisionTree, ForestParams};
+
+        #[test]
+        fn should_split_on_the_separating_threshold() {
+            let samples = vec![vec![0.1], vec![0.2], vec![0.7], vec![0.9]];
+            let labels = [false, false, true, true];
+            let mut rng = StdRng::seed_from_u64(0);
+
+            let tree = DecisionTree::grow(
+                &samples,
+                &labels,
+                vec![0, 1, 2, 3],
+                &ForestParams::default(),
+                &mut rng,
+            );
+
+            assert_eq!(tree.len(), 3);
+            assert_eq!(tree.predict(&[0.4]), 0.0);
+            assert_eq!(tree.predict(&[0.5]), 1.0);
+        }
+
+        #[test]
+        fn should_stop_at_the_maximum_depth() {
+            let samples = vec![vec![0.1], vec![0.2], vec![0.7], vec![0.9]];
+            let labels = [false, true, false, true];
+            let params = ForestParams {
+                max_depth: 0,
+                ..ForestParams::default()
+            };
+            let mut rng = StdRng::seed_from_u64(0);
+
+            let tree = DecisionTree::grow(&samples, &labels, vec![0, 1, 2, 3], &params, &mut rng);
+
+            assert_eq!(tree.len(), 1);
+            assert_eq!(tree.predict(&[0.1]), 0.5);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use super::sequence::BASES;
+use super::Motif;
+
+/// Rounds of masking tried on a region before giving up on hiding every
+/// site in it.
+pub const MAX_MASK_ATTEMPTS: usize = 100;
+
+/// Names of the mask strategies, as accepted by [`mask_strategy`].
+pub const MASK_STRATEGIES: [&str; 6] = ["random", "n", "soft", "shuffle", "point", "none"];
+
+/// How a motif site lying inside a neighbor is hidden. Sites are given
+/// 5' to 3' on the strand they were found on.
+pub trait MaskStrategy {
+    /// Name of the strategy, as accepted by [`mask_strategy`].
+    fn name(&self) -> &'static str;
+
+    /// Mask `site`, an occurrence of `motif`. A single attempt is made,
+    /// the caller checks the result and retries.
+    fn mask(&self, site: &mut [u8], motif: &Motif, rng: &mut dyn RngCore);
+
+    /// Whether masked sites stop matching the motif. Sites are only masked
+    /// once by strategies that keep them.
+    fn hides_motif(&self) -> bool {
+        true
+    }
+}
+
+/// Build the mask strategy called `name`.
+pub fn mask_strategy(name: &str) -> Option<Box<dyn MaskStrategy>> {
+    let strategy: Box<dyn MaskStrategy> = match name {
+        "random" => Box::new(RandomMask),
+        "n" => Box::new(NMask),
+        "soft" => Box::new(SoftMask),
+        "shuffle" => Box::new(ShuffleMask),
+        "point" => Box::new(PointMask),
+        "none" => Box::new(NoMask),
+        _ => return None,
+    };
+
+    Some(strategy)
+}
+
+/// Replaces every base of the site with random ones.
+pub struct RandomMask;
+
+impl MaskStrategy for RandomMask {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn mask(&self, site: &mut [u8], _motif: &Motif, rng: &mut dyn RngCore) {
+        for base in site.iter_mut() {
+            *base = BASES[rng.gen_range(0..BASES.len())] as u8;
+        }
+    }
+}
+
+/// Replaces every base of the site with `N`.
+pub struct NMask;
+
+impl MaskStrategy for NMask {
+    fn name(&self) -> &'static str {
+        "n"
+    }
+
+    fn mask(&self, site: &mut [u8], _motif: &Motif, _rng: &mut dyn RngCore) {
+        site.fill(b'N');
+    }
+}
+
+/// Lowercases the site, keeping its bases.
+pub struct SoftMask;
+
+impl MaskStrategy for SoftMask {
+    fn name(&self) -> &'static str {
+        "soft"
+    }
+
+    fn mask(&self, site: &mut [u8], _motif: &Motif, _rng: &mut dyn RngCore) {
+        site.make_ascii_lowercase();
+    }
+}
+
+/// Shuffles the bases of the site, preserving its composition.
+pub struct ShuffleMask;
+
+impl MaskStrategy for ShuffleMask {
+    fn name(&self) -> &'static str {
+        "shuffle"
+    }
+
+    fn mask(&self, site: &mut [u8], _motif: &Motif, rng: &mut dyn RngCore) {
+        site.shuffle(rng);
+    }
+}
+
+/// Mutates only the candidate adenosine of the site to another base.
+pub struct PointMask;
+
+impl MaskStrategy for PointMask {
+    fn name(&self) -> &'static str {
+        "point"
+    }
+
+    fn mask(&self, site: &mut [u8], motif: &Motif, rng: &mut dyn RngCore) {
+        const OTHERS: [u8; 3] = [b'C', b'G', b'U'];
+
+        if let Some(base) = site.get_mut(motif.anchor()) {
+            *base = OTHERS[rng.gen_range(0..OTHERS.len())];
+        }
+    }
+}
+
+/// Leaves the site untouched.
+pub struct NoMask;
+
+impl MaskStrategy for NoMask {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn mask(&self, _site: &mut [u8], _motif: &Motif, _rng: &mut dyn RngCore) {}
+
+    fn hides_motif(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod mask_strategy {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::domain::entities::mask::{mask_strategy, MASK_STRATEGIES};
+        use crate::domain::entities::Motif;
+
+        fn mask(name: &str, site: &str) -> String {
+            let mut site = site.as_bytes().to_vec();
+            let mut rng = StdRng::seed_from_u64(1);
+
+            mask_strategy(name)
+                .unwrap()
+                .mask(&mut site, &Motif::default(), &mut rng);
+
+            String::from_utf8(site).unwrap()
+        }
+
+        #[test]
+        fn should_build_every_named_strategy() {
+            for name in MASK_STRATEGIES {
+                assert_eq!(mask_strategy(name).unwrap().name(), name);
+            }
+
+            assert!(mask_strategy("blur").is_none());
+        }
+
+        #[test]
+        fn should_replace_the_site() {
+            assert_eq!(mask("n", "GGACU"), "NNNNN");
+            assert_eq!(mask("soft", "GGACU"), "ggacu");
+            assert_eq!(mask("none", "GGACU"), "GGACU");
+            assert_eq!(mask("random", "GGACU").len(), 5);
+        }
+
+        #[test]
+        fn should_keep_composition_when_shuffling() {
+            let mut shuffled: Vec<u8> = mask("shuffle", "GGACU").into_bytes();
+            shuffled.sort();
+
+            assert_eq!(shuffled, b"ACGGU");
+        }
+
+        #[test]
+        fn should_mutate_only_the_candidate_adenosine() {
+            let masked = mask("point", "GGACU");
+
+            assert_eq!(&masked[..2], "GG");
+            assert_ne!(&masked[2..3], "A");
+            assert_eq!(&masked[3..], "CU");
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use regex::Regex;
+
+use crate::Error;
+
+/// Named motifs that can be selected instead of writing the IUPAC pattern.
+pub const PRESETS: [(&str, &str); 3] = [("DRACH", "DRACH"), ("RRACH", "RRACH"), ("GGACU", "GGACU")];
+
+/// A sequence motif described by an IUPAC pattern, such as `DRACH`.
+#[derive(Clone, Debug)]
+pub struct Motif {
+    name: String,
+    pattern: String,
+    regex: Regex,
+    classes: Vec<&'static str>,
+    anchor: usize,
+}
+
+impl Motif {
+    /// Build a motif from an IUPAC pattern. Both `U` and `T` are accepted
+    /// and matched as uracil.
+    pub fn from_iupac(pattern: &str) -> Result<Self, Error> {
+        let pattern = pattern.to_uppercase().replace('T', "U");
+
+        if pattern.is_empty() {
+            return Err(Error::new("motif pattern must not be empty"));
+        }
+
+        let mut re = String::new();
+        let mut classes = vec![];
+
+        for code in pattern.chars() {
+            let bases = match iupac_bases(code) {
+                Some(bases) => bases,
+                None => {
+                    let msg = format!("'{}' is not an IUPAC nucleotide code", code);
+                    return Err(Error::new(&msg));
+                }
+            };
+
+            if bases.len() == 1 {
+                re.push_str(bases);
+            } else {
+                re.push_str(&format!("[{}]", bases));
+            }
+
+            classes.push(bases);
+        }
+
+        let regex = Regex::new(&re).unwrap();
+        let anchor = find_anchor(&pattern);
+
+        Ok(Self {
+            name: pattern.clone(),
+            pattern,
+            regex,
+            classes,
+            anchor,
+        })
+    }
+
+    /// Build one of the named motifs listed in [`PRESETS`].
+    pub fn preset(name: &str) -> Option<Self> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .map(|(preset, pattern)| {
+                let mut motif = Self::from_iupac(pattern).unwrap();
+                motif.name = String::from(*preset);
+                motif
+            })
+    }
+
+    /// Build a motif from a preset name or, failing that, an IUPAC pattern.
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        match Self::preset(value) {
+            Some(motif) => Ok(motif),
+            None => Self::from_iupac(value),
+        }
+    }
+
+    /// Get a reference to the motif's name.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Get a reference to the motif's IUPAC pattern.
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_ref()
+    }
+
+    /// Get a reference to the motif's compiled regex.
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    /// Get the offset of the candidate adenosine inside the motif.
+    pub fn anchor(&self) -> usize {
+        self.anchor
+    }
+
+    /// Number of bases matched by the motif.
+    pub fn len(&self) -> usize {
+        self.pattern.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Whether the motif occurs in `text` starting exactly at `pos`.
+    pub fn matches_at(&self, text: &str, pos: usize) -> bool {
+        let bytes = match text.as_bytes().get(pos..pos + self.len()) {
+            Some(bytes) => bytes,
+            None => return false,
+        };
+
+        bytes
+            .iter()
+            .zip(self.classes.iter())
+            .all(|(base, class)| class.as_bytes().contains(base))
+    }
+}
+
+impl Default for Motif {
+    fn default() -> Self {
+        Self::preset("DRACH").unwrap()
+    }
+}
+
+/// Bases represented by an IUPAC nucleotide code, using `U` for uracil.
+pub fn iupac_bases(code: char) -> Option<&'static str> {
+    let bases = match code.to_ascii_uppercase() {
+        'A' => "A",
+        'C' => "C",
+        'G' => "G",
+        'U' | 'T' => "U",
+        'R' => "AG",
+        'Y' => "CU",
+        'S' => "CG",
+        'W' => "AU",
+        'K' => "GU",
+        'M' => "AC",
+        'B' => "CGU",
+        'D' => "AGU",
+        'H' => "ACU",
+        'V' => "ACG",
+        'N' => "ACGU",
+        _ => return None,
+    };
+
+    Some(bases)
+}
+
+/// The candidate adenosine is the last literal `A` followed by a `C`, as in
+/// the `AC` core of DRACH, falling back to the last literal `A` and then to
+/// the middle of the motif.
+fn find_anchor(pattern: &str) -> usize {
+    let codes: Vec<char> = pattern.chars().collect();
+
+    let before_c = (0..codes.len().saturating_sub(1))
+        .rev()
+        .find(|&i| codes[i] == 'A' && codes[i + 1] == 'C');
+
+    let last_a = codes.iter().rposition(|&code| code == 'A');
+
+    before_c.or(last_a).unwrap_or(codes.len() / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    mod from_iupac {
+        use crate::domain::entities::motif::Motif;
+
+        #[test]
+        fn should_expand_degenerate_codes() {
+            let motif = Motif::from_iupac("DRACH").unwrap();
+
+            assert_eq!(motif.regex().as_str(), "[AGU][AG]AC[ACU]");
+            assert!(motif.is_match("GGACU"));
+            assert!(!motif.is_match("CGACU"));
+        }
+
+        #[test]
+        fn should_treat_t_as_u() {
+            let motif = Motif::from_iupac("ggact").unwrap();
+
+            assert_eq!(motif.pattern(), "GGACU");
+            assert!(motif.is_match("GGACU"));
+        }
+
+        #[test]
+        fn should_reject_unknown_codes() {
+            let res = Motif::from_iupac("DRXCH");
+
+            assert_eq!(
+                res.err().unwrap().to_string(),
+                "'X' is not an IUPAC nucleotide code"
+            );
+        }
+    }
+
+    mod parse {
+        use crate::domain::entities::motif::Motif;
+
+        #[test]
+        fn should_prefer_presets() {
+            let motif = Motif::parse("rrach").unwrap();
+
+            assert_eq!(motif.name(), "RRACH");
+            assert_eq!(motif.pattern(), "RRACH");
+        }
+
+        #[test]
+        fn should_locate_the_candidate_adenosine() {
+            assert_eq!(Motif::parse("DRACH").unwrap().anchor(), 2);
+            assert_eq!(Motif::parse("GGACU").unwrap().anchor(), 2);
+            assert_eq!(Motif::parse("AAGAA").unwrap().anchor(), 4);
+            assert_eq!(Motif::parse("GGG").unwrap().anchor(), 1);
+        }
+    }
+}
@@ -2,22 +2,41 @@ use std::fs::{self};
 use std::io::{self, Write};
 use std::ops::Range;
 
-use rand::Rng;
-use regex::Regex;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
 
+use crate::utils::compression::{CompressedWriter, Compression};
 use crate::utils::ends_with_any;
-use crate::Result;
-
-use super::drach::DRACH_RE;
-
-pub const ACCEPTED_FASTA_EXT: [&'static str; 2] = [".fasta", ".fas"];
+use crate::{Error, Result};
+
+use super::drach::Strand;
+use super::mask::{MaskStrategy, MAX_MASK_ATTEMPTS};
+use super::Motif;
+
+pub use fasta_reader::FastaReader;
+pub use header::{FastaHeader, IdRule};
+
+mod fasta_reader;
+mod header;
+
+pub const ACCEPTED_FASTA_EXT: [&str; 8] = [
+    ".fasta",
+    ".fas",
+    ".fa",
+    ".fna",
+    ".fasta.gz",
+    ".fas.gz",
+    ".fa.gz",
+    ".fna.gz",
+];
 pub const BASES: [char; 4] = ['A', 'U', 'G', 'C'];
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Sequence {
     id: String,
     header: String,
     payload: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     origin: Option<Box<Sequence>>,
 }
 
@@ -56,43 +75,16 @@ impl Sequence {
     }
 
     /// Get a reference to the sequence's origin.
-    pub fn origin(&self) -> Option<&Box<Sequence>> {
-        self.origin.as_ref()
+    pub fn origin(&self) -> Option<&Sequence> {
+        self.origin.as_deref()
     }
 }
 
 impl Sequence {
+    /// Load every record of a FASTA file at once. Prefer [`FastaReader`]
+    /// for large files.
     pub fn load(path: &str) -> Result<Vec<Sequence>> {
-        let fasta = fs::read_to_string(path)?;
-        let mut seqs = vec![];
-        let mut curr_seq = None;
-
-        for line in fasta.split_terminator('\n') {
-            if line.starts_with('>') {
-                if let Some(seq) = curr_seq.take() {
-                    seqs.push(seq);
-                }
-
-                let id = match &line[1..].split_once(' ') {
-                    Some((id, _)) => id,
-                    None => "",
-                };
-
-                curr_seq = Some(Sequence::new(id, line, "", None));
-
-                continue;
-            }
-
-            if let Some(ref mut seq) = curr_seq {
-                seq.payload_mut().push_str(line.trim());
-            }
-        }
-
-        if let Some(seq) = curr_seq.take() {
-            seqs.push(seq);
-        }
-
-        Ok(seqs)
+        FastaReader::open(path)?.collect()
     }
 
     pub fn save(&self, path: &str, append: bool) -> io::Result<()> {
@@ -103,19 +95,23 @@ impl Sequence {
             ));
         }
 
-        let mut file = fs::OpenOptions::new()
+        let file = fs::OpenOptions::new()
             .create(true)
             .write(true)
             .append(append)
+            .truncate(!append)
             .open(path)?;
 
-        writeln!(file, "{}", self.to_fasta(80))?;
+        let mut writer = CompressedWriter::new(file, Compression::from_path(path));
+
+        writeln!(writer, "{}", self.to_fasta(80))?;
+        writer.finish()?;
 
         Ok(())
     }
 
     pub fn to_fasta(&self, line_len: usize) -> String {
-        let mut fasta = format!(">{}", self.header());
+        let mut fasta = format!(">{}", self.header().trim_start_matches('>'));
         let payload = self.payload();
         let payload_len = payload.len();
 
@@ -130,6 +126,55 @@ impl Sequence {
     }
 }
 
+impl Sequence {
+    /// Copy of the sequence in the RNA alphabet: uppercase, with every `T`
+    /// replaced by `U`.
+    pub fn to_rna(&self) -> Sequence {
+        let payload = self.payload.to_uppercase().replace('T', "U");
+        Sequence::new(&self.id, &self.header, &payload, None)
+    }
+
+    /// Reverse complement of the sequence in the RNA alphabet. IUPAC codes
+    /// are complemented too and unknown characters are kept as they are.
+    pub fn reverse_complement(&self) -> Sequence {
+        let payload: String = self.payload.chars().rev().map(complement).collect();
+        Sequence::new(&self.id, &self.header, &payload, None)
+    }
+
+    /// Copy of the bases of `range` as read on `strand`, where reverse
+    /// strand ranges are counted on the reverse complement.
+    pub fn region(&self, range: Range<usize>, strand: Strand) -> Sequence {
+        let range = self.clamp_range(range);
+
+        match strand {
+            Strand::Forward => Sequence::new(&self.id, &self.header, &self.payload[range], None),
+            Strand::Reverse => {
+                let length = self.payload.len();
+                let payload = &self.payload[length - range.end..length - range.start];
+                Sequence::new(&self.id, &self.header, payload, None).reverse_complement()
+            }
+        }
+    }
+}
+
+fn complement(base: char) -> char {
+    match base.to_ascii_uppercase() {
+        'A' => 'U',
+        'U' | 'T' => 'A',
+        'C' => 'G',
+        'G' => 'C',
+        'R' => 'Y',
+        'Y' => 'R',
+        'K' => 'M',
+        'M' => 'K',
+        'B' => 'V',
+        'V' => 'B',
+        'D' => 'H',
+        'H' => 'D',
+        other => other,
+    }
+}
+
 impl Sequence {
     pub fn clamp_range(&self, range: Range<usize>) -> Range<usize> {
         let start = range.start.clamp(0, self.payload.len());
@@ -137,35 +182,140 @@ impl Sequence {
         start..end
     }
 
-    pub fn remove_drachs_from_range(&self, range: Range<usize>) -> String {
-        let re = Regex::new(DRACH_RE).unwrap();
+    /// Mask the site at `range` with `strategy`. Reverse strand sites are
+    /// handed to the strategy as read on the reverse strand.
+    pub fn mask_site(
+        &mut self,
+        range: Range<usize>,
+        strand: Strand,
+        motif: &Motif,
+        strategy: &dyn MaskStrategy,
+        rng: &mut dyn RngCore,
+    ) {
         let range = self.clamp_range(range);
-        let mut seq_slice: Vec<char> = self.payload[range].chars().map(|c| c).collect();
+        let mut site: Vec<u8> = self.payload[range.clone()].bytes().collect();
 
-        loop {
-            for i in 0..5 {
-                let base = rand::thread_rng().gen_range(0..=3);
-                seq_slice[i + 5] = BASES[base];
-            }
+        if strand == Strand::Reverse {
+            site = reverse_complement_bytes(&site);
+        }
 
-            let bytes: Vec<u8> = seq_slice.iter().map(|c| *c as u8).collect();
-            let new_seq_slice = String::from_utf8(bytes).unwrap();
+        strategy.mask(&mut site, motif, rng);
 
-            if !re.is_match(&new_seq_slice[4..9]) {
-                break new_seq_slice;
+        if strand == Strand::Reverse {
+            site = reverse_complement_bytes(&site);
+        }
+
+        self.payload
+            .replace_range(range, &String::from_utf8_lossy(&site));
+    }
+
+    /// Mask every occurrence of `motif` lying inside `region` on the given
+    /// `strands`, leaving the bases in `keep` untouched. The region is
+    /// scanned again after each round, since substitutions can create new
+    /// sites, and an error is returned when sites are still left after
+    /// [`MAX_MASK_ATTEMPTS`] rounds.
+    pub fn mask_sites(
+        &mut self,
+        region: Range<usize>,
+        keep: Range<usize>,
+        strands: &[Strand],
+        motif: &Motif,
+        strategy: &dyn MaskStrategy,
+        rng: &mut dyn RngCore,
+    ) -> Result {
+        let region = self.clamp_range(region);
+        let keep = self.clamp_range(keep);
+
+        for _ in 0..MAX_MASK_ATTEMPTS {
+            let sites = self.find_sites(region.clone(), keep.clone(), strands, motif);
+
+            if sites.is_empty() {
+                return Ok(());
             }
 
-            seq_slice = new_seq_slice.chars().map(|c| c).collect();
+            for (range, strand) in sites {
+                let kept = self.payload[keep.clone()].to_string();
+                self.mask_site(range, strand, motif, strategy, rng);
+                self.payload.replace_range(keep.clone(), &kept);
+            }
+
+            if !strategy.hides_motif() {
+                return Ok(());
+            }
         }
+
+        let msg = format!(
+            "Could not mask every {} site of '{}' after {} attempts",
+            motif.name(),
+            self.id,
+            MAX_MASK_ATTEMPTS
+        );
+        Err(Box::new(Error::new(&msg)))
     }
 
-    pub fn remove_drachs_from_range_mut(&mut self, range: Range<usize>) {
-        let range = self.clamp_range(range);
-        let seq_slice = self.remove_drachs_from_range(range.clone());
-        self.payload.replace_range(range, &seq_slice);
+    /// Occurrences of `motif` lying inside `region` on the given `strands`,
+    /// in forward coordinates, besides those overlapping `keep`.
+    fn find_sites(
+        &self,
+        region: Range<usize>,
+        keep: Range<usize>,
+        strands: &[Strand],
+        motif: &Motif,
+    ) -> Vec<(Range<usize>, Strand)> {
+        let mut sites = vec![];
+
+        if region.len() < motif.len() {
+            return sites;
+        }
+
+        let forward = &self.payload[region.clone()];
+
+        for strand in strands {
+            let reverse;
+            let text = match strand {
+                Strand::Forward => forward,
+                Strand::Reverse => {
+                    reverse =
+                        String::from_utf8_lossy(&reverse_complement_bytes(forward.as_bytes()))
+                            .into_owned();
+                    reverse.as_str()
+                }
+            };
+
+            for start in region.start..=region.end - motif.len() {
+                let range = start..start + motif.len();
+                let pos = match strand {
+                    Strand::Forward => range.start - region.start,
+                    Strand::Reverse => region.end - range.end,
+                };
+                let overlaps_keep = range.start < keep.end && keep.start < range.end;
+
+                if !overlaps_keep && motif.matches_at(text, pos) {
+                    sites.push((range, *strand));
+                }
+            }
+        }
+
+        sites
     }
 }
 
+/// Reverse complement of `site`, keeping the case of each base.
+fn reverse_complement_bytes(site: &[u8]) -> Vec<u8> {
+    site.iter()
+        .rev()
+        .map(|&base| {
+            let complement = complement(base as char) as u8;
+
+            if base.is_ascii_lowercase() {
+                complement.to_ascii_lowercase()
+            } else {
+                complement
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     mod to_fasta {
@@ -179,6 +329,14 @@ mod tests {
             assert_eq!(seq.to_fasta(10), expect);
         }
 
+        #[test]
+        fn should_not_double_header_marker() {
+            let seq = Sequence::new("NC_045512", ">NC_045512 Sars-Cov-2", "AGTC", None);
+            let expect = concat!(">NC_045512 Sars-Cov-2", "\nAGTC");
+
+            assert_eq!(seq.to_fasta(10), expect);
+        }
+
         #[test]
         fn should_match_format_breaking_lines() {
             let seq = Sequence::new("NC_045512", "NC_045512 Sars-Cov-2", "AGTC", None);
@@ -188,6 +346,124 @@ mod tests {
         }
     }
 
+    mod reverse_complement {
+        use crate::domain::entities::Sequence;
+
+        #[test]
+        fn should_reverse_and_complement_in_rna_alphabet() {
+            let seq = Sequence::new("id", "header", "AACGTRN", None);
+
+            assert_eq!(seq.reverse_complement().payload(), "NYACGUU");
+        }
+
+        #[test]
+        fn to_rna_should_normalise_case_and_thymine() {
+            let seq = Sequence::new("id", "header", "acgtU", None);
+
+            assert_eq!(seq.to_rna().payload(), "ACGUU");
+        }
+    }
+
+    mod mask_sites {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::domain::entities::drach::Strand;
+        use crate::domain::entities::mask::{mask_strategy, RandomMask};
+        use crate::domain::entities::{Motif, Sequence};
+
+        const BOTH: [Strand; 2] = [Strand::Forward, Strand::Reverse];
+
+        fn count_sites(seq: &Sequence, motif: &Motif) -> usize {
+            let forward = seq.payload();
+            let reverse = seq.reverse_complement();
+
+            (0..forward.len())
+                .filter(|&pos| {
+                    motif.matches_at(forward, pos) || motif.matches_at(reverse.payload(), pos)
+                })
+                .count()
+        }
+
+        #[test]
+        fn should_mask_sites_at_the_sequence_ends() {
+            let motif = Motif::default();
+
+            for payload in ["GGACU", "GGACUAGUCC", "AGGACUUAGUCCA", "GG"] {
+                let mut seq = Sequence::new("id", "header", payload, None);
+                let mut rng = StdRng::seed_from_u64(3);
+
+                seq.mask_sites(0..100, 0..0, &BOTH, &motif, &RandomMask, &mut rng)
+                    .unwrap();
+
+                assert_eq!(seq.payload().len(), payload.len());
+                assert_eq!(count_sites(&seq, &motif), 0, "{}", payload);
+            }
+        }
+
+        #[test]
+        fn should_leave_no_site_in_the_region_for_any_seed() {
+            let motif = Motif::default();
+
+            for seed in 0..200 {
+                let mut seq = Sequence::new("id", "header", "GGACUGGACAGACUGGACU", None);
+                let mut rng = StdRng::seed_from_u64(seed);
+
+                seq.mask_sites(0..19, 0..0, &BOTH, &motif, &RandomMask, &mut rng)
+                    .unwrap();
+
+                assert_eq!(count_sites(&seq, &motif), 0);
+            }
+        }
+
+        #[test]
+        fn should_keep_the_given_site() {
+            let motif = Motif::default();
+            let mut seq = Sequence::new("id", "header", "GGACUUGGACUU", None);
+            let mut rng = StdRng::seed_from_u64(3);
+
+            seq.mask_sites(
+                0..12,
+                6..11,
+                &[Strand::Forward],
+                &motif,
+                &RandomMask,
+                &mut rng,
+            )
+            .unwrap();
+
+            assert_eq!(&seq.payload()[6..11], "GGACU");
+            assert!(!motif.matches_at(seq.payload(), 0));
+        }
+
+        #[test]
+        fn should_mask_reverse_sites_on_their_strand() {
+            let motif = Motif::default();
+            let mask = mask_strategy("soft").unwrap();
+            let mut seq = Sequence::new("id", "header", "CCAGUCCAA", None);
+            let mut rng = StdRng::seed_from_u64(3);
+
+            seq.mask_sites(0..9, 0..0, &BOTH, &motif, mask.as_ref(), &mut rng)
+                .unwrap();
+
+            assert_eq!(seq.payload(), "CCaguccAA");
+        }
+
+        #[test]
+        fn should_fail_when_sites_cannot_be_hidden() {
+            let motif = Motif::parse("NN").unwrap();
+            let mut seq = Sequence::new("id", "header", "ACGU", None);
+            let mut rng = StdRng::seed_from_u64(3);
+
+            let res = seq.mask_sites(0..4, 0..0, &BOTH, &motif, &RandomMask, &mut rng);
+
+            assert_eq!(
+                res.err().unwrap().to_string(),
+                "Could not mask every NN site of 'id' after 100 attempts"
+            );
+        }
+    }
+
     mod save {
         use std::fs::{self, File};
         use std::path::Path;
@@ -266,5 +542,36 @@ mod tests {
 
             Ok(())
         }
+
+        #[test]
+        fn should_gzip_if_path_ends_with_gz() -> Result {
+            fs::create_dir_all("./tmp")?;
+
+            let mut path;
+
+            loop {
+                path = format!("./tmp/{}.fa.gz", rand::thread_rng().gen::<u32>());
+
+                if !Path::new(&path).exists() {
+                    break;
+                }
+            }
+
+            let seq = Sequence::new("NC_045512", "NC_045512 Sars-Cov-2", "AGUC", None);
+            seq.save(&path, false)?;
+            seq.save(&path, true)?;
+
+            let bytes = fs::read(&path)?;
+            let loaded = Sequence::load(&path)?;
+
+            assert_eq!(bytes[..2], [0x1f, 0x8b]);
+            assert_eq!(loaded.len(), 2);
+            assert_eq!(loaded[1].header(), seq.header());
+            assert_eq!(loaded[1].payload(), seq.payload());
+
+            fs::remove_file(path)?;
+
+            Ok(())
+        }
     }
 }
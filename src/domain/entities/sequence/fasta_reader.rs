@@ -0,0 +1,137 @@
+use std::io::{self, BufRead, BufReader};
+
+use crate::utils::compression;
+use crate::Result;
+
+use super::{FastaHeader, IdRule, Sequence};
+
+/// Path that makes [`FastaReader::open`] read from the standard input.
+pub const STDIN_PATH: &str = "-";
+
+/// Reads the records of a FASTA stream one at a time, so only the record
+/// being read is held in memory.
+pub struct FastaReader<R> {
+    reader: R,
+    line: String,
+    header: Option<String>,
+    id_rule: IdRule,
+}
+
+impl<R: BufRead> FastaReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            header: None,
+            id_rule: IdRule::default(),
+        }
+    }
+
+    /// Use `id_rule` to extract the ids of the sequences read.
+    pub fn with_id_rule(mut self, id_rule: IdRule) -> Self {
+        self.id_rule = id_rule;
+        self
+    }
+
+    /// Read the next line into the line buffer, returning false at the end
+    /// of the stream.
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        let read = self.reader.read_line(&mut self.line)?;
+        Ok(read > 0)
+    }
+
+    fn read_sequence(&mut self) -> Result<Option<Sequence>> {
+        while self.header.is_none() {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+
+            if let Some(header) = self.line.strip_prefix('>') {
+                self.header = Some(String::from(header.trim_end()));
+            }
+        }
+
+        let header = FastaHeader::parse(&self.header.take().unwrap(), self.id_rule);
+        let mut seq = Sequence::new(header.id(), header.text(), "", None);
+
+        while self.read_line()? {
+            if let Some(next_header) = self.line.strip_prefix('>') {
+                self.header = Some(String::from(next_header.trim_end()));
+                break;
+            }
+
+            // Lines starting with `;` are comments in the original FASTA
+            // format, wherever they appear.
+            if self.line.starts_with(';') {
+                continue;
+            }
+
+            seq.payload_mut().push_str(self.line.trim());
+        }
+
+        Ok(Some(seq))
+    }
+}
+
+impl FastaReader<Box<dyn BufRead>> {
+    /// Open a FASTA file, or the standard input when `path` is `-`.
+    /// Gzip and bgzip compressed input is detected and decompressed.
+    pub fn open(path: &str) -> Result<Self> {
+        let reader = if path == STDIN_PATH {
+            compression::decompress(BufReader::new(io::stdin()))?
+        } else {
+            compression::open(path)?
+        };
+
+        Ok(Self::new(reader))
+    }
+}
+
+impl<R: BufRead> Iterator for FastaReader<R> {
+    type Item = Result<Sequence>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_sequence().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod next {
+        use crate::domain::entities::sequence::FastaReader;
+        use crate::Result;
+
+        #[test]
+        fn should_yield_one_sequence_per_record() -> Result {
+            let fasta = ";comment\n>a first\nACG\nUU\r\n>b second\n\n>c third\nGG";
+            let seqs = FastaReader::new(fasta.as_bytes()).collect::<Result<Vec<_>>>()?;
+
+            let payloads: Vec<&str> = seqs.iter().map(|seq| seq.payload()).collect();
+            let ids: Vec<&str> = seqs.iter().map(|seq| seq.id()).collect();
+
+            assert_eq!(payloads, vec!["ACGUU", "", "GG"]);
+            assert_eq!(ids, vec!["a", "b", "c"]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_skip_comments_inside_records() -> Result {
+            let fasta = ">a\nACG\n;not a sequence\nUU\n;trailing\n>b\nGG\n";
+            let seqs = FastaReader::new(fasta.as_bytes()).collect::<Result<Vec<_>>>()?;
+
+            assert_eq!(seqs[0].payload(), "ACGUU");
+            assert_eq!(seqs[1].payload(), "GG");
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_yield_nothing_for_empty_input() {
+            let mut reader = FastaReader::new("".as_bytes());
+
+            assert!(reader.next().is_none());
+        }
+    }
+}
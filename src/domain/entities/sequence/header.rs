@@ -0,0 +1,180 @@
+use std::str::FromStr;
+
+use crate::Error;
+
+/// Database tags of NCBI style `db|accession` identifiers.
+const DB_TAGS: [&str; 10] = [
+    "gb", "emb", "dbj", "ref", "sp", "tr", "lcl", "pir", "prf", "pdb",
+];
+
+/// How the id of a sequence is extracted from its FASTA header.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IdRule {
+    /// The first word, unwrapping `|`-delimited NCBI, Ensembl and GENCODE
+    /// identifiers to their accession.
+    #[default]
+    Auto,
+    /// The first word, as is.
+    FirstWord,
+    /// The whole header.
+    Full,
+    /// The n-th (1-based) `|`-delimited field of the first word.
+    Field(usize),
+}
+
+impl FromStr for IdRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = match s {
+            "auto" => IdRule::Auto,
+            "first-word" => IdRule::FirstWord,
+            "full" => IdRule::Full,
+            _ => match s.strip_prefix("field:").map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => IdRule::Field(n),
+                _ => {
+                    let msg = format!(
+                        "Invalid id rule '{}', expected auto, first-word, full or field:N",
+                        s
+                    );
+                    return Err(Error::new(&msg));
+                }
+            },
+        };
+
+        Ok(rule)
+    }
+}
+
+/// A parsed FASTA header line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FastaHeader {
+    id: String,
+    description: String,
+    text: String,
+}
+
+impl FastaHeader {
+    /// Parse a header line, with or without its leading `>`.
+    pub fn parse(line: &str, rule: IdRule) -> Self {
+        let text = line.strip_prefix('>').unwrap_or(line).trim();
+
+        let (first_word, description) = match text.split_once(char::is_whitespace) {
+            Some((first_word, description)) => (first_word, description.trim_start()),
+            None => (text, ""),
+        };
+
+        let id = match rule {
+            IdRule::Auto => accession(first_word),
+            IdRule::FirstWord => first_word,
+            IdRule::Full => text,
+            IdRule::Field(n) => first_word.split('|').nth(n - 1).unwrap_or(""),
+        };
+
+        Self {
+            id: String::from(id),
+            description: String::from(description),
+            text: String::from(text),
+        }
+    }
+
+    /// Get a reference to the fasta header's id.
+    pub fn id(&self) -> &str {
+        self.id.as_ref()
+    }
+
+    /// Get a reference to the fasta header's description, the text after the
+    /// first word.
+    pub fn description(&self) -> &str {
+        self.description.as_ref()
+    }
+
+    /// Get a reference to the fasta header's text, without the leading `>`.
+    pub fn text(&self) -> &str {
+        self.text.as_ref()
+    }
+}
+
+/// Accession of a `|`-delimited identifier: the field after the database
+/// tag for NCBI style ids (`gi|1|ref|NM_001.1|`), otherwise the first
+/// non-empty field, as in Ensembl and GENCODE headers.
+fn accession(word: &str) -> &str {
+    let fields: Vec<&str> = word.split('|').collect();
+    let mut i = 0;
+
+    if fields.len() > 2 && fields[0] == "gi" {
+        i = 2;
+    }
+
+    if i + 1 < fields.len() && DB_TAGS.contains(&fields[i]) && !fields[i + 1].is_empty() {
+        return fields[i + 1];
+    }
+
+    if i > 0 {
+        return fields[1];
+    }
+
+    fields
+        .into_iter()
+        .find(|field| !field.is_empty())
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse {
+        use crate::domain::entities::sequence::header::{FastaHeader, IdRule};
+
+        fn id(line: &str, rule: IdRule) -> String {
+            String::from(FastaHeader::parse(line, rule).id())
+        }
+
+        #[test]
+        fn should_handle_headers_without_description() {
+            let header = FastaHeader::parse(">NM_001", IdRule::Auto);
+
+            assert_eq!(header.id(), "NM_001");
+            assert_eq!(header.description(), "");
+            assert_eq!(header.text(), "NM_001");
+        }
+
+        #[test]
+        fn should_split_on_tabs() {
+            let header = FastaHeader::parse(">NM_001\tsome gene", IdRule::Auto);
+
+            assert_eq!(header.id(), "NM_001");
+            assert_eq!(header.description(), "some gene");
+        }
+
+        #[test]
+        fn auto_should_unwrap_pipe_delimited_ids() {
+            assert_eq!(id(">gi|123|ref|NM_000.1| desc", IdRule::Auto), "NM_000.1");
+            assert_eq!(id(">ref|NM_000.1|", IdRule::Auto), "NM_000.1");
+            assert_eq!(id(">sp|P12345|ABC_HUMAN", IdRule::Auto), "P12345");
+            assert_eq!(
+                id(
+                    ">ENST00000456328.2|ENSG00000223972.5|-|DDX11L1-202|",
+                    IdRule::Auto
+                ),
+                "ENST00000456328.2"
+            );
+        }
+
+        #[test]
+        fn should_apply_explicit_rules() {
+            let line = ">ENST01|ENSG01|name desc";
+
+            assert_eq!(id(line, IdRule::FirstWord), "ENST01|ENSG01|name");
+            assert_eq!(id(line, IdRule::Full), "ENST01|ENSG01|name desc");
+            assert_eq!(id(line, IdRule::Field(2)), "ENSG01");
+            assert_eq!(id(line, IdRule::Field(9)), "");
+        }
+
+        #[test]
+        fn should_parse_rules() {
+            assert_eq!("field:3".parse::<IdRule>().unwrap(), IdRule::Field(3));
+            assert!("field:0".parse::<IdRule>().is_err());
+            assert!("word".parse::<IdRule>().is_err());
+        }
+    }
+}
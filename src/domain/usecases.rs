@@ -1 +1,12 @@
+pub mod encode_features;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod header_template;
+pub mod model;
+pub mod records;
+pub mod scan_drachs;
+pub mod train_forest;
 pub mod write_drach_neighbor;
+pub mod write_features;
+pub mod write_report;
+pub mod write_sites;
@@ -0,0 +1,407 @@
+use std::ops::RangeInclusive;
+
+use crate::domain::entities::drach::{
+    DrachContext, DrachNeighbor, DrachNeighborPosition, PaddingPolicy,
+};
+use crate::domain::entities::{Drach, Motif};
+use crate::{Error, Result};
+
+pub use kmer::{KmerEncoder, KsnpfEncoder};
+pub use physicochemical::{EiipEncoder, EnacEncoder, NcpEncoder, PseKncEncoder};
+
+mod kmer;
+mod physicochemical;
+
+/// Names of the encodings, as accepted by [`feature_encoder`]. `kmer` and
+/// `ksnpf` take an optional range, as in `kmer:1-3` or `ksnpf:0-5`, `enac`
+/// an optional width and `pseknc` an optional k, as in `enac:5`.
+pub const ENCODINGS: [&str; 8] = [
+    "onehot", "binary", "kmer", "ksnpf", "ncp", "eiip", "enac", "pseknc",
+];
+
+/// Order of the bases in per base encodings.
+const BASE_ORDER: [char; 4] = ['A', 'C', 'G', 'U'];
+
+/// Turns the window around a site into numeric features.
+pub trait FeatureEncoder {
+    /// Name of the encoding, as accepted by [`feature_encoder`].
+    fn name(&self) -> &'static str;
+
+    /// Names of the features of a window of `length` bases.
+    fn columns(&self, length: usize) -> Vec<String>;
+
+    /// Append the features of `window` to `row`.
+    fn encode(&self, window: &str, row: &mut Vec<f64>);
+}
+
+/// Build the encoder called `name`, which may be followed by `:` and the
+/// parameter it takes.
+pub fn feature_encoder(name: &str) -> Option<Box<dyn FeatureEncoder>> {
+    let (name, range) = match name.split_once(':') {
+        Some((name, range)) => (name, Some(range)),
+        None => (name, None),
+    };
+
+    let encoder: Box<dyn FeatureEncoder> = match (name, range) {
+        ("onehot", None) => Box::new(OneHotEncoder),
+        ("binary", None) => Box::new(BinaryEncoder),
+        ("kmer", None) => Box::new(KmerEncoder::new(1..=3)),
+        ("kmer", Some(range)) => {
+            let ks = parse_range(range)?;
+
+            if *ks.start() == 0 || *ks.end() > kmer::MAX_K {
+                return None;
+            }
+
+            Box::new(KmerEncoder::new(ks))
+        }
+        ("ksnpf", None) => Box::new(KsnpfEncoder::new(0..=5)),
+        ("ksnpf", Some(range)) => {
+            let gaps = parse_range(range)?;
+
+            if *gaps.end() > kmer::MAX_GAP {
+                return None;
+            }
+
+            Box::new(KsnpfEncoder::new(gaps))
+        }
+        ("ncp", None) => Box::new(NcpEncoder),
+        ("eiip", None) => Box::new(EiipEncoder),
+        ("enac", None) => Box::new(EnacEncoder::new(5)),
+        ("enac", Some(width)) => match width.parse() {
+            Ok(width) if width > 0 => Box::new(EnacEncoder::new(width)),
+            _ => return None,
+        },
+        ("pseknc", param) => {
+            let k = match param {
+                Some(k) => k.parse().ok()?,
+                None => 2,
+            };
+
+            if k == 0 || k > kmer::MAX_K {
+                return None;
+            }
+
+            Box::new(PseKncEncoder::new(
+                k,
+                PseKncEncoder::DEFAULT_LAMBDA,
+                PseKncEncoder::DEFAULT_WEIGHT,
+            ))
+        }
+        _ => return None,
+    };
+
+    Some(encoder)
+}
+
+/// Parse `n` or `start-end` as an inclusive range.
+fn parse_range(range: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let start = start.parse().ok()?;
+    let end = end.parse().ok()?;
+
+    if start > end {
+        return None;
+    }
+
+    Some(start..=end)
+}
+
+/// Several encodings of the same window, written one after the other.
+pub struct EncodeFeatures {
+    encoders: Vec<Box<dyn FeatureEncoder>>,
+}
+
+impl EncodeFeatures {
+    pub fn new(encoders: Vec<Box<dyn FeatureEncoder>>) -> Self {
+        Self { encoders }
+    }
+
+    /// Build the encoders of a comma separated list of names, such as
+    /// `onehot,binary`.
+    pub fn parse(names: &str) -> Result<Self> {
+        let mut encoders = vec![];
+
+        for name in names.split(',').map(str::trim) {
+            match feature_encoder(name) {
+                Some(encoder) => encoders.push(encoder),
+                None => {
+                    let msg = format!(
+                        "Unknown encoding '{}', expected one of: {}",
+                        name,
+                        ENCODINGS.join(", ")
+                    );
+                    return Err(Box::new(Error::new(&msg)));
+                }
+            }
+        }
+
+        Ok(Self::new(encoders))
+    }
+
+    /// Names of the features of a window of `length` bases.
+    pub fn columns(&self, length: usize) -> Vec<String> {
+        self.encoders
+            .iter()
+            .flat_map(|encoder| encoder.columns(length))
+            .collect()
+    }
+
+    /// Check that every encoding gives features for a window of `length`
+    /// bases.
+    pub fn check(&self, length: usize) -> Result {
+        match self
+            .encoders
+            .iter()
+            .find(|encoder| encoder.columns(length).is_empty())
+        {
+            Some(encoder) => {
+                let msg = format!(
+                    "Encoding '{}' gives no features for windows of {} bases",
+                    encoder.name(),
+                    length
+                );
+                Err(Box::new(Error::new(&msg)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Features of `window`, in the order of [`EncodeFeatures::columns`].
+    pub fn encode(&self, window: &str) -> Vec<f64> {
+        let mut row = vec![];
+
+        for encoder in self.encoders.iter() {
+            encoder.encode(window, &mut row);
+        }
+
+        row
+    }
+}
+
+/// Encodes the window around each site, made of its flanks and the motif.
+pub struct SiteEncoder {
+    encode: EncodeFeatures,
+    left_length: usize,
+    right_length: usize,
+    padding: PaddingPolicy,
+    seed: u64,
+}
+
+impl SiteEncoder {
+    pub fn new(
+        encode: EncodeFeatures,
+        left_length: usize,
+        right_length: usize,
+        padding: PaddingPolicy,
+        seed: u64,
+    ) -> Self {
+        Self {
+            encode,
+            left_length,
+            right_length,
+            padding,
+            seed,
+        }
+    }
+
+    /// Names of the features of windows around sites of `motif`.
+    pub fn columns(&self, motif: &Motif) -> Vec<String> {
+        self.encode.columns(self.window_length(motif))
+    }
+
+    /// Check that every encoding gives features for windows around sites of
+    /// `motif`.
+    pub fn check(&self, motif: &Motif) -> Result {
+        self.encode.check(self.window_length(motif))
+    }
+
+    /// Window around `drach` along with its features, or `None` if the
+    /// padding policy skips it.
+    pub fn encode<'a>(
+        &self,
+        drach: &'a Drach,
+        ctx: &DrachContext<'a>,
+    ) -> Result<Option<(DrachNeighbor<'a>, Vec<f64>)>> {
+        let mut builder = DrachNeighbor::builder();
+        let neighbor = builder
+            .set_drach(drach)
+            .set_context(ctx.clone())
+            .set_position(DrachNeighborPosition::Window)
+            .set_left_length(self.left_length)
+            .set_right_length(self.right_length)
+            .set_padding(self.padding)
+            .set_seed(self.seed)
+            .build()?;
+
+        if neighbor.is_skipped() {
+            return Ok(None);
+        }
+
+        let (window, _) = neighbor.render()?;
+        let expected = self.window_length(ctx.motif());
+
+        if window.len() != expected {
+            let msg = format!(
+                "Window of site {} of '{}' is {} bases long, expected {}",
+                drach.index(),
+                ctx.sequence().id(),
+                window.len(),
+                expected
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        let values = self.encode.encode(&window);
+        Ok(Some((neighbor, values)))
+    }
+
+    fn window_length(&self, motif: &Motif) -> usize {
+        self.left_length + motif.len() + self.right_length
+    }
+}
+
+/// Four values per base, one for each of A, C, G and U. Ambiguous bases
+/// spread evenly as 0.25.
+pub struct OneHotEncoder;
+
+impl FeatureEncoder for OneHotEncoder {
+    fn name(&self) -> &'static str {
+        "onehot"
+    }
+
+    fn columns(&self, length: usize) -> Vec<String> {
+        per_base_columns(self.name(), length)
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        for base in window.chars() {
+            match base_index(base) {
+                Some(index) => row.extend((0..4).map(|i| if i == index { 1.0 } else { 0.0 })),
+                None => row.extend([0.25; 4]),
+            }
+        }
+    }
+}
+
+/// Four bits per base, one for each of A, C, G and U. Ambiguous bases are
+/// all zeros.
+pub struct BinaryEncoder;
+
+impl FeatureEncoder for BinaryEncoder {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn columns(&self, length: usize) -> Vec<String> {
+        per_base_columns(self.name(), length)
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        for base in window.chars() {
+            let index = base_index(base);
+            row.extend((0..4).map(|i| if Some(i) == index { 1.0 } else { 0.0 }));
+        }
+    }
+}
+
+/// Index of `base` in [`BASE_ORDER`], reading `T` as `U` and ignoring case
+/// so soft masked bases keep their value.
+fn base_index(base: char) -> Option<usize> {
+    let base = match base.to_ascii_uppercase() {
+        'T' => 'U',
+        other => other,
+    };
+
+    BASE_ORDER.iter().position(|&b| b == base)
+}
+
+/// Columns named `{encoding}_{position}_{base}`, with 1-based positions.
+fn per_base_columns(encoding: &str, length: usize) -> Vec<String> {
+    (1..=length)
+        .flat_map(|position| {
+            BASE_ORDER
+                .iter()
+                .map(move |base| format!("{}_{}_{}", encoding, position, base))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod encode_features {
+        use crate::domain::usecases::encode_features::EncodeFeatures;
+
+        #[test]
+        fn should_encode_bases_in_acgu_order() {
+            let onehot = EncodeFeatures::parse("onehot").unwrap();
+            let binary = EncodeFeatures::parse("binary").unwrap();
+
+            assert_eq!(
+                onehot.encode("AuN"),
+                [1., 0., 0., 0., 0., 0., 0., 1., 0.25, 0.25, 0.25, 0.25]
+            );
+            assert_eq!(
+                binary.encode("GTN"),
+                [0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0.]
+            );
+        }
+
+        #[test]
+        fn should_combine_encodings_in_order() {
+            let features = EncodeFeatures::parse("onehot, binary").unwrap();
+            let columns = features.columns(2);
+
+            assert_eq!(columns.len(), 16);
+            assert_eq!(columns[0], "onehot_1_A");
+            assert_eq!(columns[7], "onehot_2_U");
+            assert_eq!(columns[8], "binary_1_A");
+            assert_eq!(features.encode("AC").len(), columns.len());
+        }
+
+        #[test]
+        fn should_reject_unknown_encodings() {
+            let res = EncodeFeatures::parse("onehot,blosum");
+
+            assert_eq!(
+                res.err().unwrap().to_string(),
+                "Unknown encoding 'blosum', expected one of: onehot, binary, kmer, ksnpf, ncp, \
+                 eiip, enac, pseknc"
+            );
+        }
+
+        #[test]
+        fn should_parse_the_range_of_kmer_encodings() {
+            let columns = |names| EncodeFeatures::parse(names).unwrap().columns(10).len();
+
+            assert_eq!(columns("kmer"), 4 + 16 + 64);
+            assert_eq!(columns("kmer:2"), 16);
+            assert_eq!(columns("kmer:1-2,ksnpf:0-2"), 20 + 3 * 16);
+            assert!(EncodeFeatures::parse("kmer:7").is_err());
+            assert!(EncodeFeatures::parse("kmer:3-1").is_err());
+            assert!(EncodeFeatures::parse("ksnpf:0-50").is_ok());
+            assert!(EncodeFeatures::parse("ksnpf:0-100000").is_err());
+            assert!(EncodeFeatures::parse("onehot:2").is_err());
+        }
+
+        #[test]
+        fn should_reject_encodings_without_features_for_the_window() {
+            let features = EncodeFeatures::parse("onehot,enac:6").unwrap();
+
+            assert!(features.check(6).is_ok());
+            assert_eq!(
+                features.check(5).err().unwrap().to_string(),
+                "Encoding 'enac' gives no features for windows of 5 bases"
+            );
+        }
+
+        #[test]
+        fn should_combine_every_encoding() {
+            let features = EncodeFeatures::parse("onehot,ncp,eiip,enac:3,pseknc,kmer:1").unwrap();
+            let columns = features.columns(11);
+
+            assert_eq!(columns.len(), 44 + 33 + 11 + 9 * 4 + 19 + 4);
+            assert_eq!(features.encode("CCGGACUUUAA").len(), columns.len());
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::ops::RangeInclusive;
+
+use super::{base_index, FeatureEncoder, BASE_ORDER};
+
+/// Largest k accepted by [`KmerEncoder`].
+pub const MAX_K: usize = 6;
+
+/// Largest gap accepted by [`KsnpfEncoder`], past the windows sites are
+/// usually given.
+pub const MAX_GAP: usize = 50;
+
+/// Frequency of every k-mer of the window, for each k of a range. K-mers
+/// are listed in lexicographic ACGU order and counted over every position
+/// of the window, so those with ambiguous bases lower the others.
+pub struct KmerEncoder {
+    ks: RangeInclusive<usize>,
+}
+
+impl KmerEncoder {
+    pub fn new(ks: RangeInclusive<usize>) -> Self {
+        Self { ks }
+    }
+}
+
+impl FeatureEncoder for KmerEncoder {
+    fn name(&self) -> &'static str {
+        "kmer"
+    }
+
+    fn columns(&self, _length: usize) -> Vec<String> {
+        self.ks
+            .clone()
+            .flat_map(kmers)
+            .map(|kmer| format!("kmer_{}", kmer))
+            .collect()
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        let bases: Vec<Option<usize>> = window.chars().map(base_index).collect();
+
+        for k in self.ks.clone() {
+            let mut counts = vec![0.0; 4usize.pow(k as u32)];
+            let positions = (bases.len() + 1).saturating_sub(k);
+
+            for kmer in bases.windows(k) {
+                if let Some(index) = kmer_index(kmer) {
+                    counts[index] += 1.0;
+                }
+            }
+
+            row.extend(counts.iter().map(|count| frequency(*count, positions)));
+        }
+    }
+}
+
+/// Frequency of every pair of bases lying `gap` positions apart, for each
+/// gap of a range, as in KSNPF.
+pub struct KsnpfEncoder {
+    gaps: RangeInclusive<usize>,
+}
+
+impl KsnpfEncoder {
+    pub fn new(gaps: RangeInclusive<usize>) -> Self {
+        Self { gaps }
+    }
+}
+
+impl FeatureEncoder for KsnpfEncoder {
+    fn name(&self) -> &'static str {
+        "ksnpf"
+    }
+
+    fn columns(&self, _length: usize) -> Vec<String> {
+        self.gaps
+            .clone()
+            .flat_map(|gap| {
+                kmers(2).map(move |pair| {
+                    let (first, second) = pair.split_at(1);
+                    format!("ksnpf_{}{}{}", first, "x".repeat(gap), second)
+                })
+            })
+            .collect()
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        let bases: Vec<Option<usize>> = window.chars().map(base_index).collect();
+
+        for gap in self.gaps.clone() {
+            let mut counts = [0.0; 16];
+            let positions = bases.len().saturating_sub(gap + 1);
+
+            for i in 0..positions {
+                if let (Some(first), Some(second)) = (bases[i], bases[i + gap + 1]) {
+                    counts[first * 4 + second] += 1.0;
+                }
+            }
+
+            row.extend(counts.iter().map(|count| frequency(*count, positions)));
+        }
+    }
+}
+
+/// Every k-mer, in lexicographic ACGU order.
+pub(super) fn kmers(k: usize) -> impl Iterator<Item = String> {
+    (0..4usize.pow(k as u32)).map(move |index| {
+        (0..k)
+            .rev()
+            .map(|i| BASE_ORDER[(index >> (2 * i)) & 3])
+            .collect()
+    })
+}
+
+/// Index of `kmer` in the order of [`kmers`], or `None` if it holds an
+/// ambiguous base.
+pub(super) fn kmer_index(kmer: &[Option<usize>]) -> Option<usize> {
+    kmer.iter()
+        .try_fold(0, |index, base| base.map(|base| index * 4 + base))
+}
+
+pub(super) fn frequency(count: f64, positions: usize) -> f64 {
+    if positions == 0 {
+        return 0.0;
+    }
+
+    count / positions as f64
+}
+
+#[cfg(test)]
+mod tests {
+    mod kmer_encoder {
+        use crate::domain::usecases::encode_features::kmer::KmerEncoder;
+        use crate::domain::usecases::encode_features::FeatureEncoder;
+
+        #[test]
+        fn should_name_columns_in_lexicographic_order() {
+            let columns = KmerEncoder::new(1..=2).columns(10);
+
+            assert_eq!(columns.len(), 4 + 16);
+            assert_eq!(
+                &columns[..5],
+                ["kmer_A", "kmer_C", "kmer_G", "kmer_U", "kmer_AA"]
+            );
+            assert_eq!(columns[19], "kmer_UU");
+        }
+
+        #[test]
+        fn should_count_kmers_over_every_position() {
+            let mut row = vec![];
+            KmerEncoder::new(2..=2).encode("GGACN", &mut row);
+
+            assert_eq!(row.len(), 16);
+            assert_eq!(row[1], 0.25); // AC
+            assert_eq!(row[10], 0.25); // GG
+            assert_eq!(row[8], 0.25); // GA
+            assert_eq!(row.iter().sum::<f64>(), 0.75);
+        }
+    }
+
+    mod ksnpf_encoder {
+        use crate::domain::usecases::encode_features::kmer::KsnpfEncoder;
+        use crate::domain::usecases::encode_features::FeatureEncoder;
+
+        #[test]
+        fn should_name_columns_with_their_gap() {
+            let columns = KsnpfEncoder::new(0..=1).columns(10);
+
+            assert_eq!(columns.len(), 32);
+            assert_eq!(columns[0], "ksnpf_AA");
+            assert_eq!(columns[17], "ksnpf_AxC");
+        }
+
+        #[test]
+        fn should_count_pairs_lying_gap_bases_apart() {
+            let mut row = vec![];
+            KsnpfEncoder::new(1..=1).encode("GGACU", &mut row);
+
+            // GxA, GxC and AxU over 3 positions.
+            assert_eq!(row[8], 1.0 / 3.0);
+            assert_eq!(row[9], 1.0 / 3.0);
+            assert_eq!(row[3], 1.0 / 3.0);
+            assert_eq!(row.iter().sum::<f64>(), 1.0);
+        }
+    }
+}
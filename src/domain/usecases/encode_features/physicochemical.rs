@@ -0,0 +1,250 @@
+use super::kmer::{frequency, kmer_index, kmers};
+use super::{base_index, FeatureEncoder, BASE_ORDER};
+
+/// Ring structure, functional group and hydrogen bond strength of A, C, G
+/// and U: purines have two rings, A and C an amino group, and A and U form
+/// weak bonds.
+const NCP: [[f64; 3]; 4] = [[1., 1., 1.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]];
+
+const NCP_NAMES: [&str; 3] = ["ring", "amino", "weak"];
+
+/// Electron-ion interaction pseudopotential of A, C, G and U.
+const EIIP: [f64; 4] = [0.1260, 0.1340, 0.0806, 0.1335];
+
+/// Chemical properties of each base, one value per base. Ambiguous bases
+/// are all zeros.
+pub struct NcpEncoder;
+
+impl FeatureEncoder for NcpEncoder {
+    fn name(&self) -> &'static str {
+        "ncp"
+    }
+
+    fn columns(&self, length: usize) -> Vec<String> {
+        (1..=length)
+            .flat_map(|position| NCP_NAMES.map(|name| format!("ncp_{}_{}", position, name)))
+            .collect()
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        for base in window.chars() {
+            match base_index(base) {
+                Some(index) => row.extend(NCP[index]),
+                None => row.extend([0.; 3]),
+            }
+        }
+    }
+}
+
+/// Electron-ion interaction pseudopotential of each base. Ambiguous bases
+/// are zero.
+pub struct EiipEncoder;
+
+impl FeatureEncoder for EiipEncoder {
+    fn name(&self) -> &'static str {
+        "eiip"
+    }
+
+    fn columns(&self, length: usize) -> Vec<String> {
+        (1..=length)
+            .map(|position| format!("eiip_{}", position))
+            .collect()
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        row.extend(
+            window
+                .chars()
+                .map(|base| base_index(base).map_or(0., |index| EIIP[index])),
+        );
+    }
+}
+
+/// Composition of A, C, G and U in every stretch of `width` bases sliding
+/// along the window.
+pub struct EnacEncoder {
+    width: usize,
+}
+
+impl EnacEncoder {
+    pub fn new(width: usize) -> Self {
+        Self { width }
+    }
+}
+
+impl FeatureEncoder for EnacEncoder {
+    fn name(&self) -> &'static str {
+        "enac"
+    }
+
+    fn columns(&self, length: usize) -> Vec<String> {
+        (1..=(length + 1).saturating_sub(self.width))
+            .flat_map(|start| BASE_ORDER.map(|base| format!("enac_{}_{}", start, base)))
+            .collect()
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        let bases: Vec<Option<usize>> = window.chars().map(base_index).collect();
+
+        for stretch in bases.windows(self.width) {
+            let mut counts = [0.; 4];
+
+            for index in stretch.iter().flatten() {
+                counts[*index] += 1.;
+            }
+
+            row.extend(counts.map(|count| frequency(count, self.width)));
+        }
+    }
+}
+
+/// Pseudo k-tuple nucleotide composition: k-mer frequencies followed by
+/// `lambda` correlation factors between bases 1 to `lambda` positions
+/// apart, all normalised together. Correlations use the NCP and EIIP
+/// properties, standardised over the four bases.
+pub struct PseKncEncoder {
+    k: usize,
+    lambda: usize,
+    weight: f64,
+}
+
+impl PseKncEncoder {
+    pub const DEFAULT_LAMBDA: usize = 3;
+    pub const DEFAULT_WEIGHT: f64 = 0.5;
+
+    pub fn new(k: usize, lambda: usize, weight: f64) -> Self {
+        Self { k, lambda, weight }
+    }
+
+    /// Mean squared difference of the standardised properties of two bases.
+    fn correlation(properties: &[[f64; 4]], first: usize, second: usize) -> f64 {
+        let sum: f64 = properties
+            .iter()
+            .map(|values| (values[first] - values[second]).powi(2))
+            .sum();
+
+        sum / properties.len() as f64
+    }
+}
+
+impl FeatureEncoder for PseKncEncoder {
+    fn name(&self) -> &'static str {
+        "pseknc"
+    }
+
+    fn columns(&self, _length: usize) -> Vec<String> {
+        kmers(self.k)
+            .map(|kmer| format!("pseknc_{}", kmer))
+            .chain((1..=self.lambda).map(|lag| format!("pseknc_lag{}", lag)))
+            .collect()
+    }
+
+    fn encode(&self, window: &str, row: &mut Vec<f64>) {
+        let bases: Vec<Option<usize>> = window.chars().map(base_index).collect();
+        let properties = standardized_properties();
+
+        let mut counts = vec![0.; 4usize.pow(self.k as u32)];
+        let positions = (bases.len() + 1).saturating_sub(self.k);
+
+        for kmer in bases.windows(self.k) {
+            if let Some(index) = kmer_index(kmer) {
+                counts[index] += 1.;
+            }
+        }
+
+        let frequencies: Vec<f64> = counts.iter().map(|c| frequency(*c, positions)).collect();
+
+        let correlations: Vec<f64> = (1..=self.lambda)
+            .map(|lag| {
+                let positions = bases.len().saturating_sub(lag);
+                let sum: f64 = (0..positions)
+                    .filter_map(|i| Some((bases[i]?, bases[i + lag]?)))
+                    .map(|(first, second)| Self::correlation(&properties, first, second))
+                    .sum();
+
+                frequency(sum, positions)
+            })
+            .collect();
+
+        let total =
+            frequencies.iter().sum::<f64>() + self.weight * correlations.iter().sum::<f64>();
+
+        if total == 0. {
+            row.extend(std::iter::repeat_n(
+                0.,
+                frequencies.len() + correlations.len(),
+            ));
+            return;
+        }
+
+        row.extend(frequencies.iter().map(|f| f / total));
+        row.extend(correlations.iter().map(|c| self.weight * c / total));
+    }
+}
+
+/// NCP and EIIP properties of A, C, G and U, each shifted and scaled to
+/// zero mean and unit variance over the four bases.
+fn standardized_properties() -> Vec<[f64; 4]> {
+    let mut properties: Vec<[f64; 4]> = (0..3)
+        .map(|property| [0, 1, 2, 3].map(|base| NCP[base][property]))
+        .collect();
+    properties.push(EIIP);
+
+    for values in properties.iter_mut() {
+        let mean = values.iter().sum::<f64>() / 4.;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 4.;
+        let deviation = variance.sqrt();
+
+        for value in values.iter_mut() {
+            *value = (*value - mean) / deviation;
+        }
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    mod physicochemical {
+        use crate::domain::usecases::encode_features::physicochemical::{
+            EiipEncoder, EnacEncoder, NcpEncoder, PseKncEncoder,
+        };
+        use crate::domain::usecases::encode_features::FeatureEncoder;
+
+        fn encode(encoder: &dyn FeatureEncoder, window: &str) -> Vec<f64> {
+            let mut row = vec![];
+            encoder.encode(window, &mut row);
+            assert_eq!(row.len(), encoder.columns(window.len()).len());
+            row
+        }
+
+        #[test]
+        fn should_encode_base_properties() {
+            assert_eq!(encode(&NcpEncoder, "AN"), [1., 1., 1., 0., 0., 0.]);
+            assert_eq!(encode(&EiipEncoder, "GCU"), [0.0806, 0.1340, 0.1335]);
+            assert_eq!(
+                NcpEncoder.columns(1),
+                ["ncp_1_ring", "ncp_1_amino", "ncp_1_weak"]
+            );
+        }
+
+        #[test]
+        fn should_slide_composition_windows() {
+            let encoder = EnacEncoder::new(2);
+
+            assert_eq!(encoder.columns(3)[4], "enac_2_A");
+            assert_eq!(encode(&encoder, "AAC"), [1., 0., 0., 0., 0.5, 0.5, 0., 0.]);
+        }
+
+        #[test]
+        fn should_normalise_composition_and_correlations_together() {
+            let encoder = PseKncEncoder::new(2, 3, 0.5);
+            let row = encode(&encoder, "GGACUGGACU");
+
+            assert_eq!(row.len(), 16 + 3);
+            assert!((row.iter().sum::<f64>() - 1.).abs() < 1e-9);
+            assert!(row[16..].iter().all(|c| *c > 0.));
+            assert_eq!(encode(&encoder, "AAAAAA")[16..], [0., 0., 0.]);
+        }
+    }
+}
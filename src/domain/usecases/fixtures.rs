@@ -0,0 +1,33 @@
+//! Test fixtures shared by the use case tests.
+
+use crate::domain::entities::drach::DrachContext;
+use crate::domain::entities::{Drach, Motif, Sequence};
+
+/// The sequence with a single site the use case tests write.
+pub const PAYLOAD: &str = "CCGGACUUU";
+
+/// A sequence scanned for the sites of the default motif.
+pub struct Scanned {
+    pub seq: Sequence,
+    pub motif: Motif,
+    pub drachs: Vec<Drach>,
+}
+
+impl Scanned {
+    pub fn new(id: &str, payload: &str) -> Self {
+        let seq = Sequence::new(id, id, payload, None);
+        let motif = Motif::default();
+        let drachs = Drach::from_motif(&seq, &motif);
+        Self { seq, motif, drachs }
+    }
+
+    pub fn context(&self) -> DrachContext<'_> {
+        DrachContext::new(&self.seq, &self.drachs, &self.motif)
+    }
+}
+
+impl Default for Scanned {
+    fn default() -> Self {
+        Self::new("seq1", PAYLOAD)
+    }
+}
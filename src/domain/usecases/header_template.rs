@@ -0,0 +1,211 @@
+use crate::domain::entities::drach::{DrachNeighbor, MaskedBase};
+use crate::Error;
+
+/// Header written when no template is given: a unique name for the record,
+/// followed by the site coordinates, motif and masked bases.
+pub const DEFAULT_HEADER_TEMPLATE: &str =
+    "{id}|{index}|{side} {start}-{end}({strand}) motif={motif} masked={masked_bases}";
+
+/// Values that can be placed in a FASTA header.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HeaderField {
+    /// Id of the source sequence.
+    Id,
+    /// Index of the site in its sequence.
+    Index,
+    /// 0-based start of the site on the forward strand.
+    Start,
+    /// End, exclusive, of the site on the forward strand.
+    End,
+    /// Strand of the site, `+` or `-`.
+    Strand,
+    /// Side of the site the record lies on: left, right or window.
+    Side,
+    /// Site sequence.
+    Motif,
+    /// Number of bases masked to hide other sites.
+    Masked,
+    /// Bases masked to hide other sites, as `position:original>replacement`.
+    MaskedBases,
+    /// Length of the record sequence.
+    Length,
+}
+
+impl HeaderField {
+    pub const ALL: [HeaderField; 10] = [
+        HeaderField::Id,
+        HeaderField::Index,
+        HeaderField::Start,
+        HeaderField::End,
+        HeaderField::Strand,
+        HeaderField::Side,
+        HeaderField::Motif,
+        HeaderField::Masked,
+        HeaderField::MaskedBases,
+        HeaderField::Length,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HeaderField::Id => "id",
+            HeaderField::Index => "index",
+            HeaderField::Start => "start",
+            HeaderField::End => "end",
+            HeaderField::Strand => "strand",
+            HeaderField::Side => "side",
+            HeaderField::Motif => "motif",
+            HeaderField::Masked => "masked",
+            HeaderField::MaskedBases => "masked_bases",
+            HeaderField::Length => "length",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.name() == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    Field(HeaderField),
+}
+
+/// A FASTA header with `{field}` placeholders, such as
+/// `{id}|{index}|{side}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderTemplate {
+    segments: Vec<Segment>,
+}
+
+impl HeaderTemplate {
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        let mut segments = vec![];
+        let mut rest = template.strip_prefix('>').unwrap_or(template);
+
+        while let Some(open) = rest.find('{') {
+            let close = match rest[open..].find('}') {
+                Some(close) => open + close,
+                None => return Err(Error::new("Unclosed '{' in header template")),
+            };
+
+            let name = &rest[open + 1..close];
+            let field = match HeaderField::from_name(name) {
+                Some(field) => field,
+                None => {
+                    let msg = format!("Unknown header placeholder '{{{}}}'", name);
+                    return Err(Error::new(&msg));
+                }
+            };
+
+            if open > 0 {
+                segments.push(Segment::Text(String::from(&rest[..open])));
+            }
+
+            segments.push(Segment::Field(field));
+            rest = &rest[close + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Text(String::from(rest)));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Header of `neighbor`, written as `sequence` after the `masked` bases
+    /// were masked. The leading `>` is not included.
+    pub fn render(
+        &self,
+        neighbor: &DrachNeighbor,
+        sequence: &str,
+        masked: &[MaskedBase],
+    ) -> String {
+        let drach = neighbor.drach();
+        let mut header = String::new();
+
+        for segment in self.segments.iter() {
+            let field = match segment {
+                Segment::Text(text) => {
+                    header.push_str(text);
+                    continue;
+                }
+                Segment::Field(field) => field,
+            };
+
+            let value = match field {
+                HeaderField::Id => neighbor.context().sequence().id().to_string(),
+                HeaderField::Index => drach.index().to_string(),
+                HeaderField::Start => drach.start().to_string(),
+                HeaderField::End => drach.end().to_string(),
+                HeaderField::Strand => drach.strand().symbol().to_string(),
+                HeaderField::Side => neighbor.position().name().to_string(),
+                HeaderField::Motif => drach.payload().to_string(),
+                HeaderField::Masked => masked.len().to_string(),
+                HeaderField::MaskedBases => MaskedBase::format_list(masked),
+                HeaderField::Length => sequence.len().to_string(),
+            };
+
+            header.push_str(&value);
+        }
+
+        header
+    }
+}
+
+impl Default for HeaderTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_HEADER_TEMPLATE).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse {
+        use crate::domain::usecases::header_template::HeaderTemplate;
+
+        #[test]
+        fn should_reject_unknown_and_unclosed_placeholders() {
+            let unknown = HeaderTemplate::parse("{id}|{site}");
+            let unclosed = HeaderTemplate::parse("{id");
+
+            assert_eq!(
+                unknown.err().unwrap().to_string(),
+                "Unknown header placeholder '{site}'"
+            );
+            assert_eq!(
+                unclosed.err().unwrap().to_string(),
+                "Unclosed '{' in header template"
+            );
+        }
+    }
+
+    mod render {
+        use crate::domain::entities::drach::{DrachNeighbor, DrachNeighborPosition, MaskedBase};
+        use crate::domain::usecases::fixtures::Scanned;
+        use crate::domain::usecases::header_template::HeaderTemplate;
+
+        #[test]
+        fn should_fill_placeholders() {
+            let scanned = Scanned::default();
+            let neighbor = DrachNeighbor::builder()
+                .set_drach(&scanned.drachs[0])
+                .set_context(scanned.context())
+                .set_position(DrachNeighborPosition::Right)
+                .set_length(2)
+                .build()
+                .unwrap();
+
+            let masked = [MaskedBase::new(0, 'A', 'U'), MaskedBase::new(1, 'C', 'U')];
+            let default = HeaderTemplate::default().render(&neighbor, "UU", &[]);
+            let custom = HeaderTemplate::parse(">{id}:{start}-{end} len={length} n={masked}")
+                .unwrap()
+                .render(&neighbor, "UU", &masked);
+            let masked = HeaderTemplate::default().render(&neighbor, "UU", &masked);
+
+            assert_eq!(default, "seq1|0|right 2-7(+) motif=GGACU masked=-");
+            assert_eq!(custom, "seq1:2-7 len=2 n=2");
+            assert_eq!(masked, "seq1|0|right 2-7(+) motif=GGACU masked=0:A>U,1:C>U");
+        }
+    }
+}
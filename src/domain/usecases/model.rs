@@ -0,0 +1,493 @@
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{NAME, VERSION};
+use crate::domain::entities::drach::PaddingPolicy;
+use crate::domain::entities::forest::{ForestParams, RandomForest};
+use crate::domain::entities::Motif;
+use crate::domain::usecases::encode_features::{EncodeFeatures, SiteEncoder};
+use crate::{Error, Result};
+
+/// Value of the `format` field of every model file.
+pub const MODEL_FORMAT: &str = "rustsramp-model";
+
+/// Version of the model file format written by [`ForestModel::write`].
+pub const MODEL_FORMAT_VERSION: u64 = 1;
+
+/// How the windows around sites are turned into features.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EncodingSettings {
+    /// Comma separated names of the encodings.
+    pub names: String,
+    pub left_length: usize,
+    pub right_length: usize,
+    pub padding: PaddingPolicy,
+    /// Name of the strategy masking sites inside their windows.
+    pub mask: String,
+}
+
+impl EncodingSettings {
+    /// Encoder of the windows around sites.
+    pub fn site_encoder(&self, seed: u64) -> Result<SiteEncoder> {
+        Ok(SiteEncoder::new(
+            EncodeFeatures::parse(&self.names)?,
+            self.left_length,
+            self.right_length,
+            self.padding,
+            seed,
+        ))
+    }
+}
+
+/// Where a model comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrainingInfo {
+    tool: String,
+    version: String,
+    source: String,
+    labels: String,
+    samples: usize,
+    positives: usize,
+    params: ForestParams,
+}
+
+impl TrainingInfo {
+    pub fn new(
+        source: &str,
+        labels: &str,
+        samples: usize,
+        positives: usize,
+        params: ForestParams,
+    ) -> Self {
+        Self {
+            tool: String::from(NAME),
+            version: String::from(VERSION),
+            source: String::from(source),
+            labels: String::from(labels),
+            samples,
+            positives,
+            params,
+        }
+    }
+
+    /// Get the version of the tool that trained the model.
+    pub fn version(&self) -> &str {
+        self.version.as_ref()
+    }
+
+    /// Get the number of labelled sites the model was trained on.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Get the number of modified sites among the samples.
+    pub fn positives(&self) -> usize {
+        self.positives
+    }
+
+    /// Get the settings the forest was grown with.
+    pub fn params(&self) -> &ForestParams {
+        &self.params
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct MotifDefinition {
+    name: String,
+    pattern: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ModelHeader {
+    format: String,
+    version: u64,
+    motif: MotifDefinition,
+    encoding: EncodingSettings,
+    features: usize,
+    training: TrainingInfo,
+}
+
+/// A trained forest along with the settings its features were built with,
+/// so sites are encoded the same way when predicting.
+///
+/// Models are written by `train` and read by `predict`. A model file is a
+/// single JSON document with two members:
+///
+/// - `header`, describing how the model was made:
+///   - `format`: always [`MODEL_FORMAT`];
+///   - `version`: the [`MODEL_FORMAT_VERSION`] the file follows;
+///   - `motif`: `name` and IUPAC `pattern` of the motif whose sites were
+///     scored;
+///   - `encoding`: the comma separated `names` of the encodings, as given
+///     to `--encoding`, the `left_length` and `right_length` of the flanks,
+///     the `padding` policy and the `mask` strategy hiding the sites inside
+///     their windows;
+///   - `features`: the number of features of each window;
+///   - `training`: the `tool` and its `version`, the `source` sequences and
+///     `labels` file, the number of labelled `samples` and of `positives`
+///     among them, and the forest `params`, whose `seed` also masks sites
+///     when predicting.
+/// - `forest`, the model parameters: the number of `features` and the
+///   `trees`, each a flat list of `nodes` whose root comes first. A node is
+///   either a `split`, sending samples whose `feature` is at most
+///   `threshold` to the node at index `left` and the others to `right`, or
+///   a `leaf` holding the `probability` of a modified site.
+///
+/// The version is bumped whenever a change would make older readers
+/// misinterpret a file, and files of other versions are refused.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ForestModel {
+    header: ModelHeader,
+    forest: RandomForest,
+}
+
+impl ForestModel {
+    pub fn new(
+        motif: &Motif,
+        encoding: EncodingSettings,
+        training: TrainingInfo,
+        forest: RandomForest,
+    ) -> Self {
+        let header = ModelHeader {
+            format: String::from(MODEL_FORMAT),
+            version: MODEL_FORMAT_VERSION,
+            motif: MotifDefinition {
+                name: String::from(motif.name()),
+                pattern: String::from(motif.pattern()),
+            },
+            encoding,
+            features: forest.features(),
+            training,
+        };
+
+        Self { header, forest }
+    }
+
+    /// Read a model file, refusing files of another format or version,
+    /// malformed trees and models whose encoding does not give the features
+    /// of their forest.
+    pub fn read(input: impl Read) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_reader(input)?;
+        let header = &value["header"];
+
+        if header["format"] != MODEL_FORMAT {
+            let msg = format!("Not a {} model file", NAME);
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        match header["version"].as_u64() {
+            Some(MODEL_FORMAT_VERSION) => {}
+            Some(version) => {
+                let msg = format!(
+                    "Unsupported model format version {}, expected {}",
+                    version, MODEL_FORMAT_VERSION
+                );
+                return Err(Box::new(Error::new(&msg)));
+            }
+            None => return Err(Box::new(Error::new("Model file has no format version"))),
+        }
+
+        let model: Self = serde_json::from_value(value)?;
+        model.forest.check()?;
+
+        let columns = model.site_encoder(None)?.columns(&model.motif()?).len();
+
+        if columns != model.header.features {
+            let msg = format!(
+                "Model encoding gives {} features but its header records {}",
+                columns, model.header.features
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        if columns != model.forest.features() {
+            let msg = format!(
+                "Model encoding gives {} features but its forest expects {}",
+                columns,
+                model.forest.features()
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        Ok(model)
+    }
+
+    pub fn write(&self, out: &mut dyn Write) -> Result {
+        serde_json::to_writer(&mut *out, self)?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Refuse `encoding` unless it gives the same features as the model's.
+    pub fn check_encoding(&self, encoding: &str) -> Result {
+        let length = self.header.encoding.left_length
+            + self.header.motif.pattern.len()
+            + self.header.encoding.right_length;
+        let expected = EncodeFeatures::parse(&self.header.encoding.names)?.columns(length);
+
+        if EncodeFeatures::parse(encoding)?.columns(length) != expected {
+            let msg = format!(
+                "Model was trained with encoding '{}' but --encoding is '{}'",
+                self.header.encoding.names, encoding
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        Ok(())
+    }
+
+    /// Refuse flank lengths differing from the model's. `None` stands for
+    /// the model's own length.
+    pub fn check_flanks(&self, left_length: Option<usize>, right_length: Option<usize>) -> Result {
+        let encoding = &self.header.encoding;
+        let left_length = left_length.unwrap_or(encoding.left_length);
+        let right_length = right_length.unwrap_or(encoding.right_length);
+
+        if (left_length, right_length) != (encoding.left_length, encoding.right_length) {
+            let msg = format!(
+                "Model was trained with {} and {} base flanks but {} and {} were given",
+                encoding.left_length, encoding.right_length, left_length, right_length
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        Ok(())
+    }
+
+    /// Refuse a `motif` of another pattern than the model's.
+    pub fn check_motif(&self, motif: &Motif) -> Result {
+        if motif.pattern() != self.header.motif.pattern {
+            let msg = format!(
+                "Model was trained on {} sites but --motif is {}",
+                self.header.motif.name,
+                motif.name()
+            );
+            return Err(Box::new(Error::new(&msg)));
+        }
+
+        Ok(())
+    }
+
+    /// Encoder of the windows around sites, as used during training. Sites
+    /// are masked with `seed`, or by default with the training seed, so the
+    /// same input is always scored the same way.
+    pub fn site_encoder(&self, seed: Option<u64>) -> Result<SiteEncoder> {
+        let seed = seed.unwrap_or(self.header.training.params.seed);
+        self.header.encoding.site_encoder(seed)
+    }
+
+    /// Motif whose sites the model scores.
+    pub fn motif(&self) -> Result<Motif> {
+        let definition = &self.header.motif;
+
+        match Motif::parse(&definition.name)? {
+            motif if motif.pattern() == definition.pattern => Ok(motif),
+            _ => Ok(Motif::from_iupac(&definition.pattern)?),
+        }
+    }
+
+    /// Get the version of the model file format.
+    pub fn version(&self) -> u64 {
+        self.header.version
+    }
+
+    /// Get a reference to the model's encoding settings.
+    pub fn encoding(&self) -> &EncodingSettings {
+        &self.header.encoding
+    }
+
+    /// Get a reference to the model's training metadata.
+    pub fn training(&self) -> &TrainingInfo {
+        &self.header.training
+    }
+
+    /// Get a reference to the model's forest.
+    pub fn forest(&self) -> &RandomForest {
+        &self.forest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod forest_model {
+        use std::io::Cursor;
+
+        use crate::domain::entities::drach::{DrachContext, PaddingPolicy};
+        use crate::domain::entities::forest::{ForestParams, RandomForest};
+        use crate::domain::entities::{Drach, Motif, Sequence};
+        use crate::domain::usecases::model::{EncodingSettings, ForestModel, TrainingInfo};
+
+        fn model() -> ForestModel {
+            model_with_flanks(1, 2)
+        }
+
+        fn model_with_flanks(left_length: usize, right_length: usize) -> ForestModel {
+            let motif = Motif::default();
+            let features = left_length + motif.len() + right_length;
+            let encoding = EncodingSettings {
+                names: String::from("eiip"),
+                left_length,
+                right_length,
+                padding: PaddingPolicy::Pad('N'),
+                mask: String::from("random"),
+            };
+            let samples = vec![vec![0.; features], vec![1.; features]];
+            let params = ForestParams {
+                trees: 2,
+                ..ForestParams::default()
+            };
+            let forest = RandomForest::train(&samples, &[false, true], &params).unwrap();
+            let training = TrainingInfo::new("in.fa", "labels.tsv", 2, 1, params);
+
+            ForestModel::new(&motif, encoding, training, forest)
+        }
+
+        fn write(model: &ForestModel) -> String {
+            let mut out = vec![];
+            model.write(&mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        }
+
+        #[test]
+        fn should_read_the_model_it_wrote() {
+            let model = model();
+            let text = write(&model);
+
+            assert!(text.starts_with(r#"{"header":{"format":"rustsramp-model","version":1,"#));
+            assert_eq!(ForestModel::read(Cursor::new(text)).unwrap(), model);
+        }
+
+        #[test]
+        fn should_refuse_other_formats_and_versions() {
+            let text = write(&model());
+            let newer = text.replace(r#""version":1"#, r#""version":2"#);
+            let error = |text: &str| ForestModel::read(Cursor::new(text.to_string())).err();
+
+            assert_eq!(
+                error(&newer).unwrap().to_string(),
+                "Unsupported model format version 2, expected 1"
+            );
+            assert_eq!(
+                error(r#"{"trees":[]}"#).unwrap().to_string(),
+                "Not a rustsramp model file"
+            );
+        }
+
+        #[test]
+        fn should_refuse_corrupted_trees() {
+            let corrupt = |path: &str, value: serde_json::Value| {
+                let mut json: serde_json::Value = serde_json::from_str(&write(&model())).unwrap();
+                *json.pointer_mut(path).unwrap() = value;
+                let text = json.to_string();
+
+                ForestModel::read(Cursor::new(text))
+                    .err()
+                    .unwrap()
+                    .to_string()
+            };
+            let split = "/forest/trees/0/nodes/0/split";
+
+            assert_eq!(
+                corrupt("/forest/trees/1/nodes", serde_json::json!([])),
+                "Tree 1 has no nodes"
+            );
+            assert_eq!(
+                corrupt(&format!("{}/left", split), serde_json::json!(0)),
+                "Node 0 of tree 0 points to node 0, expected one after it among the 3 nodes"
+            );
+            assert_eq!(
+                corrupt(&format!("{}/right", split), serde_json::json!(9)),
+                "Node 0 of tree 0 points to node 9, expected one after it among the 3 nodes"
+            );
+            assert_eq!(
+                corrupt(&format!("{}/feature", split), serde_json::json!(8)),
+                "Node 0 of tree 0 splits on feature 8 but there are 8 features"
+            );
+        }
+
+        #[test]
+        fn should_mask_sites_with_the_training_seed_by_default() {
+            let model = model_with_flanks(10, 10);
+            let seq = Sequence::new("s", "s", "UUUUUUUUUUGGACUGGACUGGACUUUUUUUUUU", None);
+            let motif = model.motif().unwrap();
+            let drachs = Drach::from_motif(&seq, &motif);
+            let ctx = DrachContext::new(&seq, &drachs, &motif);
+
+            let predict = |seed| -> Vec<f64> {
+                let sites = model.site_encoder(seed).unwrap();
+
+                drachs
+                    .iter()
+                    .map(|drach| {
+                        let (_, values) = sites.encode(drach, &ctx).unwrap().unwrap();
+                        model.forest().predict(&values)
+                    })
+                    .collect()
+            };
+
+            assert_eq!(predict(None), predict(None));
+            assert_eq!(predict(None), predict(Some(model.training().params().seed)));
+        }
+
+        #[test]
+        fn should_refuse_a_forest_of_another_encoding() {
+            let text = write(&model()).replace(r#""names":"eiip""#, r#""names":"onehot""#);
+
+            assert_eq!(
+                ForestModel::read(Cursor::new(text))
+                    .err()
+                    .unwrap()
+                    .to_string(),
+                "Model encoding gives 32 features but its header records 8"
+            );
+        }
+
+        #[test]
+        fn should_report_which_feature_count_differs() {
+            let read = |path: &str| {
+                let mut json: serde_json::Value = serde_json::from_str(&write(&model())).unwrap();
+                *json.pointer_mut(path).unwrap() = serde_json::json!(9);
+
+                ForestModel::read(Cursor::new(json.to_string()))
+                    .err()
+                    .unwrap()
+                    .to_string()
+            };
+
+            assert_eq!(
+                read("/header/features"),
+                "Model encoding gives 8 features but its header records 9"
+            );
+            assert_eq!(
+                read("/forest/features"),
+                "Model encoding gives 8 features but its forest expects 9"
+            );
+        }
+
+        #[test]
+        fn should_refuse_mismatched_settings() {
+            let model = model();
+
+            assert!(model.check_encoding(" eiip").is_ok());
+            assert!(model.check_flanks(Some(1), None).is_ok());
+            assert!(model.check_motif(&Motif::parse("DRACH").unwrap()).is_ok());
+            assert_eq!(
+                model.check_encoding("onehot").err().unwrap().to_string(),
+                "Model was trained with encoding 'eiip' but --encoding is 'onehot'"
+            );
+            assert_eq!(
+                model.check_flanks(Some(2), None).err().unwrap().to_string(),
+                "Model was trained with 1 and 2 base flanks but 2 and 2 were given"
+            );
+            assert_eq!(
+                model
+                    .check_motif(&Motif::parse("GGACU").unwrap())
+                    .err()
+                    .unwrap()
+                    .to_string(),
+                "Model was trained on DRACH sites but --motif is GGACU"
+            );
+        }
+    }
+}
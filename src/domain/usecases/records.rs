@@ -0,0 +1,378 @@
+use std::cell::Cell;
+use std::io::{BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{NAME, VERSION};
+use crate::domain::entities::drach::{
+    DrachNeighbor, DrachNeighborPosition, MaskedBase, PaddingPolicy,
+};
+use crate::domain::entities::{Drach, Sequence};
+use crate::Result;
+
+use super::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+
+/// The run a record comes from, so results can be traced back to the input
+/// and settings that produced them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    tool: String,
+    version: String,
+    source: String,
+    motif: String,
+    pattern: String,
+    scan_mode: ScanMode,
+    strand_mode: StrandMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mask: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+impl Provenance {
+    pub fn new(source: &str, scan_drachs: &ScanDrachs, seed: Option<u64>) -> Self {
+        let motif = scan_drachs.motif();
+
+        Self {
+            tool: String::from(NAME),
+            version: String::from(VERSION),
+            source: String::from(source),
+            motif: String::from(motif.name()),
+            pattern: String::from(motif.pattern()),
+            scan_mode: scan_drachs.mode(),
+            strand_mode: scan_drachs.strand_mode(),
+            mask: None,
+            seed,
+        }
+    }
+
+    /// Record the name of the strategy the records were masked with.
+    #[must_use]
+    pub fn with_mask(self, mask: &str) -> Self {
+        Self {
+            mask: Some(String::from(mask)),
+            ..self
+        }
+    }
+
+    /// Get a reference to the provenance's tool version.
+    pub fn version(&self) -> &str {
+        self.version.as_ref()
+    }
+
+    /// Get a reference to the provenance's source file.
+    pub fn source(&self) -> &str {
+        self.source.as_ref()
+    }
+
+    /// Get a reference to the provenance's motif name.
+    pub fn motif(&self) -> &str {
+        self.motif.as_ref()
+    }
+
+    /// Get a reference to the provenance's motif pattern.
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_ref()
+    }
+
+    /// Get the provenance's scan mode.
+    pub fn scan_mode(&self) -> ScanMode {
+        self.scan_mode
+    }
+
+    /// Get the provenance's strand mode.
+    pub fn strand_mode(&self) -> StrandMode {
+        self.strand_mode
+    }
+
+    /// Get the name of the strategy the provenance's records were masked
+    /// with, if they were masked at all.
+    pub fn mask(&self) -> Option<&str> {
+        self.mask.as_deref()
+    }
+
+    /// Get the seed the provenance's records were masked with, if they were
+    /// masked at all.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+}
+
+/// A site found in a sequence.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SiteRecord {
+    id: String,
+    sequence_length: usize,
+    site: Drach,
+    provenance: Provenance,
+}
+
+impl SiteRecord {
+    pub fn new(seq: &Sequence, drach: &Drach, provenance: &Provenance) -> Self {
+        Self {
+            id: String::from(seq.id()),
+            sequence_length: seq.payload().len(),
+            site: drach.clone(),
+            provenance: provenance.clone(),
+        }
+    }
+
+    /// Get a reference to the site record's sequence id.
+    pub fn id(&self) -> &str {
+        self.id.as_ref()
+    }
+
+    /// Get the site record's sequence length.
+    pub fn sequence_length(&self) -> usize {
+        self.sequence_length
+    }
+
+    /// Get a reference to the site record's site.
+    pub fn site(&self) -> &Drach {
+        &self.site
+    }
+
+    /// Get a reference to the site record's provenance.
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+}
+
+/// A neighbor as written by the extract command, with the settings used to
+/// build it and how many of its bases were masked.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NeighborRecord {
+    id: String,
+    sequence_length: usize,
+    site: Drach,
+    position: DrachNeighborPosition,
+    left_length: usize,
+    right_length: usize,
+    padding: PaddingPolicy,
+    sequence: String,
+    truncated: bool,
+    masked: usize,
+    masked_bases: Vec<MaskedBase>,
+    provenance: Provenance,
+}
+
+impl NeighborRecord {
+    pub fn new(neighbor: &DrachNeighbor, provenance: &Provenance) -> Result<Self> {
+        let seq = neighbor.context().sequence();
+        let (sequence, masked) = neighbor.render()?;
+
+        Ok(Self {
+            id: String::from(seq.id()),
+            sequence_length: seq.payload().len(),
+            site: neighbor.drach().clone(),
+            position: neighbor.position(),
+            left_length: neighbor.left_length(),
+            right_length: neighbor.right_length(),
+            padding: neighbor.padding(),
+            sequence,
+            truncated: neighbor.is_truncated(),
+            masked: masked.len(),
+            masked_bases: masked,
+            provenance: provenance.clone(),
+        })
+    }
+
+    /// Get a reference to the neighbor record's sequence id.
+    pub fn id(&self) -> &str {
+        self.id.as_ref()
+    }
+
+    /// Get the neighbor record's sequence length.
+    pub fn sequence_length(&self) -> usize {
+        self.sequence_length
+    }
+
+    /// Get a reference to the neighbor record's site.
+    pub fn site(&self) -> &Drach {
+        &self.site
+    }
+
+    /// Get the neighbor record's position.
+    pub fn position(&self) -> DrachNeighborPosition {
+        self.position
+    }
+
+    /// Get the neighbor record's left flank length.
+    pub fn left_length(&self) -> usize {
+        self.left_length
+    }
+
+    /// Get the neighbor record's right flank length.
+    pub fn right_length(&self) -> usize {
+        self.right_length
+    }
+
+    /// Get the neighbor record's padding policy.
+    pub fn padding(&self) -> PaddingPolicy {
+        self.padding
+    }
+
+    /// Get a reference to the neighbor record's sequence, as written.
+    pub fn sequence(&self) -> &str {
+        self.sequence.as_ref()
+    }
+
+    /// Whether the neighbor crossed one of the sequence ends.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Get the number of masked bases of the neighbor record.
+    pub fn masked(&self) -> usize {
+        self.masked
+    }
+
+    /// Get a reference to the neighbor record's provenance.
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+}
+
+/// Writes records either as a single JSON array or as JSON Lines, one
+/// record per line.
+pub(crate) struct JsonStream {
+    lines: bool,
+    is_first: Cell<bool>,
+}
+
+impl JsonStream {
+    pub fn new(lines: bool) -> Self {
+        Self {
+            lines,
+            is_first: Cell::new(true),
+        }
+    }
+
+    pub fn begin(&self, out: &mut dyn Write) -> Result {
+        if !self.lines {
+            write!(out, "[")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write<T: Serialize>(&self, out: &mut dyn Write, record: &T) -> Result {
+        let json = serde_json::to_string(record)?;
+
+        if self.lines {
+            writeln!(out, "{}", json)?;
+        } else if self.is_first.replace(false) {
+            write!(out, "\n{}", json)?;
+        } else {
+            write!(out, ",\n{}", json)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn end(&self, out: &mut dyn Write) -> Result {
+        if !self.lines {
+            writeln!(out, "\n]")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read records written as a JSON array or as JSON Lines.
+pub fn read_records<T: DeserializeOwned>(mut reader: impl BufRead) -> Result<Vec<T>> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+
+    if text.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(&text)?);
+    }
+
+    let mut records = vec![];
+
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        records.push(serde_json::from_str(line)?);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    mod read_records {
+        use std::io::Cursor;
+
+        use crate::domain::entities::{Drach, Motif, Sequence};
+        use crate::domain::usecases::records::{read_records, JsonStream, Provenance, SiteRecord};
+        use crate::domain::usecases::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+        use crate::Result;
+
+        fn site_records() -> Vec<SiteRecord> {
+            let motif = Motif::default();
+            let scan_drachs = ScanDrachs::new(&motif, ScanMode::default(), StrandMode::Both);
+            let seq = scan_drachs.prepare(Sequence::new("seq1", "seq1", "CCGGACUUAGUCCAA", None));
+            let provenance = Provenance::new("in.fasta", &scan_drachs, Some(42));
+
+            scan_drachs
+                .scan(&seq)
+                .iter()
+                .map(|drach| SiteRecord::new(&seq, drach, &provenance))
+                .collect()
+        }
+
+        fn write(records: &[SiteRecord], lines: bool) -> Result<Vec<u8>> {
+            let stream = JsonStream::new(lines);
+            let mut out = vec![];
+
+            stream.begin(&mut out)?;
+            for record in records.iter() {
+                stream.write(&mut out, record)?;
+            }
+            stream.end(&mut out)?;
+
+            Ok(out)
+        }
+
+        #[test]
+        fn should_round_trip_json_and_json_lines() -> Result {
+            let records = site_records();
+
+            for lines in [false, true] {
+                let json = write(&records, lines)?;
+                let read: Vec<SiteRecord> = read_records(Cursor::new(json))?;
+
+                assert_eq!(read, records);
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_write_one_record_per_line() -> Result {
+            let json = String::from_utf8(write(&site_records(), true)?)?;
+            let first = json.lines().next().unwrap();
+
+            assert_eq!(json.lines().count(), 2);
+            assert!(first.starts_with(
+                r#"{"id":"seq1","sequence_length":15,"site":{"payload":"GGACU","index":0,"start":2,"end":7,"strand":"forward"}"#
+            ));
+            assert!(
+                first.contains(r#""scan_mode":"non-overlapping","strand_mode":"both","seed":42}"#)
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_read_empty_inputs() -> Result {
+            let read: Vec<Drach> = read_records(Cursor::new("[\n]\n"))?;
+            assert!(read.is_empty());
+
+            let read: Vec<Drach> = read_records(Cursor::new(""))?;
+            assert!(read.is_empty());
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::entities::{
+    drach::{DrachPosition, Strand},
+    Drach, Motif, Sequence,
+};
+
+/// How occurrences of a motif that share bases are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScanMode {
+    /// Leftmost occurrences only; a site starting inside a previous one is
+    /// not reported.
+    #[default]
+    NonOverlapping,
+    /// Every start position is tested, so overlapping sites are all
+    /// reported.
+    Overlapping,
+    /// Overlapping sites are merged into a single site spanning all of them.
+    Collapsed,
+}
+
+/// Strands of the sequence that are scanned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StrandMode {
+    /// Only the sequence as given, assumed to be the sense strand.
+    #[default]
+    Forward,
+    /// The sequence and its reverse complement.
+    Both,
+}
+
+pub struct ScanDrachs<'a> {
+    motif: &'a Motif,
+    mode: ScanMode,
+    strand_mode: StrandMode,
+}
+
+impl<'a> ScanDrachs<'a> {
+    pub fn new(motif: &'a Motif, mode: ScanMode, strand_mode: StrandMode) -> Self {
+        Self {
+            motif,
+            mode,
+            strand_mode,
+        }
+    }
+
+    /// Get a reference to the scan drachs' motif.
+    pub fn motif(&self) -> &Motif {
+        self.motif
+    }
+
+    /// Get the scan drachs' mode.
+    pub fn mode(&self) -> ScanMode {
+        self.mode
+    }
+
+    /// Get the scan drachs' strand mode.
+    pub fn strand_mode(&self) -> StrandMode {
+        self.strand_mode
+    }
+
+    /// Prepare a sequence for scanning. Sequences scanned on both strands
+    /// are normalised to the RNA alphabet so DNA input is matched as well.
+    pub fn prepare(&self, sequence: Sequence) -> Sequence {
+        match self.strand_mode {
+            StrandMode::Forward => sequence,
+            StrandMode::Both => sequence.to_rna(),
+        }
+    }
+
+    /// Find the motif sites of the sequence, sorted by their forward strand
+    /// start. Reverse strand sites are mapped back to forward coordinates.
+    pub fn scan(&self, sequence: &Sequence) -> Vec<Drach> {
+        let forward = self.scan_strand(sequence);
+
+        if self.strand_mode == StrandMode::Forward {
+            return forward;
+        }
+
+        let length = sequence.payload().len();
+        let reverse = self
+            .scan_strand(&sequence.reverse_complement())
+            .into_iter()
+            .map(|d| {
+                (
+                    String::from(d.payload()),
+                    length - d.end(),
+                    length - d.start(),
+                )
+            });
+
+        let mut sites: Vec<(String, usize, usize, Strand)> = forward
+            .iter()
+            .map(|d| {
+                (
+                    String::from(d.payload()),
+                    d.start(),
+                    d.end(),
+                    Strand::Forward,
+                )
+            })
+            .chain(reverse.map(|(payload, start, end)| (payload, start, end, Strand::Reverse)))
+            .collect();
+
+        sites.sort_by_key(|(_, start, end, strand)| (*start, *end, *strand == Strand::Reverse));
+
+        sites
+            .into_iter()
+            .enumerate()
+            .map(|(index, (payload, start, end, strand))| {
+                Drach::with_strand(payload, DrachPosition::new(index, start, end), strand)
+            })
+            .collect()
+    }
+
+    fn scan_strand(&self, sequence: &Sequence) -> Vec<Drach> {
+        match self.mode {
+            ScanMode::NonOverlapping => Drach::from_motif(sequence, self.motif),
+            ScanMode::Overlapping => self.scan_overlapping(sequence),
+            ScanMode::Collapsed => self.scan_collapsed(sequence),
+        }
+    }
+
+    fn scan_overlapping(&self, sequence: &Sequence) -> Vec<Drach> {
+        let text = sequence.payload();
+        let len = self.motif.len();
+
+        (0..text.len())
+            .filter(|&start| self.motif.matches_at(text, start))
+            .enumerate()
+            .map(|(index, start)| {
+                let payload = String::from(&text[start..start + len]);
+                Drach::new(payload, DrachPosition::new(index, start, start + len))
+            })
+            .collect()
+    }
+
+    fn scan_collapsed(&self, sequence: &Sequence) -> Vec<Drach> {
+        let text = sequence.payload();
+        let mut ranges: Vec<(usize, usize)> = vec![];
+
+        for drach in self.scan_overlapping(sequence) {
+            match ranges.last_mut() {
+                Some((_, end)) if drach.start() < *end => *end = drach.end(),
+                _ => ranges.push((drach.start(), drach.end())),
+            }
+        }
+
+        ranges
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end))| {
+                let payload = String::from(&text[start..end]);
+                Drach::new(payload, DrachPosition::new(index, start, end))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod scan {
+        use crate::domain::entities::{Motif, Sequence};
+        use crate::domain::usecases::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+
+        const PAYLOAD: &str = "CCGGACAGACUCC";
+
+        fn spans(mode: ScanMode) -> Vec<(usize, usize, String)> {
+            let motif = Motif::default();
+            let seq = Sequence::new("id", "header", PAYLOAD, None);
+
+            ScanDrachs::new(&motif, mode, StrandMode::Forward)
+                .scan(&seq)
+                .iter()
+                .map(|d| (d.start(), d.end(), String::from(d.payload())))
+                .collect()
+        }
+
+        #[test]
+        fn non_overlapping_should_miss_sites_sharing_bases() {
+            assert_eq!(
+                spans(ScanMode::NonOverlapping),
+                vec![(2, 7, "GGACA".into())]
+            );
+        }
+
+        #[test]
+        fn overlapping_should_test_every_start_position() {
+            assert_eq!(
+                spans(ScanMode::Overlapping),
+                vec![(2, 7, "GGACA".into()), (6, 11, "AGACU".into())]
+            );
+        }
+
+        #[test]
+        fn collapsed_should_merge_overlapping_sites() {
+            assert_eq!(
+                spans(ScanMode::Collapsed),
+                vec![(2, 11, "GGACAGACU".into())]
+            );
+        }
+    }
+
+    mod strands {
+        use crate::domain::entities::drach::{
+            DrachContext, DrachNeighbor, DrachNeighborPosition, Strand,
+        };
+        use crate::domain::entities::{Motif, Sequence};
+        use crate::domain::usecases::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+
+        #[test]
+        fn should_map_reverse_sites_to_forward_coordinates() {
+            let motif = Motif::default();
+            let scan_drachs = ScanDrachs::new(&motif, ScanMode::NonOverlapping, StrandMode::Both);
+            // AGUCC is the reverse complement of GGACU.
+            let seq = scan_drachs.prepare(Sequence::new("id", "header", "ccaGTCCtt", None));
+            let drachs = scan_drachs.scan(&seq);
+
+            assert_eq!(drachs.len(), 1);
+            assert_eq!(drachs[0].strand(), Strand::Reverse);
+            assert_eq!(drachs[0].payload(), "GGACU");
+            assert_eq!((drachs[0].start(), drachs[0].end()), (2, 7));
+        }
+
+        #[test]
+        fn reverse_neighbors_should_be_read_on_the_reverse_strand() {
+            let motif = Motif::default();
+            let scan_drachs = ScanDrachs::new(&motif, ScanMode::NonOverlapping, StrandMode::Both);
+            let seq = Sequence::new("id", "header", "CCAGUCCUU", None);
+            let drachs = scan_drachs.scan(&seq);
+            let ctx = DrachContext::new(&seq, &drachs, &motif);
+
+            let window = DrachNeighbor::builder()
+                .set_drach(&drachs[0])
+                .set_context(ctx)
+                .set_position(DrachNeighborPosition::Window)
+                .set_length(2)
+                .build()
+                .unwrap();
+
+            assert_eq!(window.render().unwrap().0, "AAGGACUGG");
+        }
+    }
+}
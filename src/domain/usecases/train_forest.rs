@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use crate::domain::entities::drach::{DrachContext, Strand};
+use crate::domain::entities::forest::{ForestParams, RandomForest};
+use crate::domain::entities::Drach;
+use crate::domain::usecases::encode_features::SiteEncoder;
+use crate::{Error, Result};
+
+/// Labels of sites, by sequence id, 0-based forward strand start and
+/// strand.
+pub type SiteLabels = HashMap<(String, usize, Strand), bool>;
+
+/// Read tab separated `id`, `start`, `label` and optional `strand` lines,
+/// where the label is `1` or `true` for modified sites and `0` or `false`
+/// for the others, and the strand `+`, the default, or `-`. Blank lines,
+/// `#` comments and a header starting with `id` are ignored.
+pub fn read_labels(input: impl BufRead) -> Result<SiteLabels> {
+    let mut labels = SiteLabels::new();
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+
+        if line.trim().is_empty() || line.starts_with('#') || fields[0] == "id" {
+            continue;
+        }
+
+        let (id, start, label, strand) = match fields.as_slice() {
+            [id, start, label] => (*id, *start, *label, "+"),
+            [id, start, label, strand] => (*id, *start, *label, *strand),
+            _ => ("", "", "", ""),
+        };
+
+        let label = match label {
+            "1" | "true" => Some(true),
+            "0" | "false" => Some(false),
+            _ => None,
+        };
+
+        let strand = match strand {
+            "+" => Some(Strand::Forward),
+            "-" => Some(Strand::Reverse),
+            _ => None,
+        };
+
+        match (start.parse().ok(), label, strand) {
+            (Some(start), Some(label), Some(strand)) => {
+                labels.insert((id.to_string(), start, strand), label)
+            }
+            _ => {
+                let msg = format!(
+                    "Invalid label on line {}: '{}', expected id, start, 0 or 1 and an \
+                     optional strand",
+                    number + 1,
+                    line
+                );
+                return Err(Box::new(Error::new(&msg)));
+            }
+        };
+    }
+
+    Ok(labels)
+}
+
+/// Collects the features of labelled sites and grows a forest on them.
+pub struct TrainForest {
+    sites: SiteEncoder,
+    labels: SiteLabels,
+    samples: Vec<Vec<f64>>,
+    targets: Vec<bool>,
+}
+
+impl TrainForest {
+    pub fn new(sites: SiteEncoder, labels: SiteLabels) -> Self {
+        Self {
+            sites,
+            labels,
+            samples: vec![],
+            targets: vec![],
+        }
+    }
+
+    /// Add the labelled sites among `drachs`.
+    pub fn add(&mut self, drachs: &[Drach], ctx: &DrachContext) -> Result {
+        let id = ctx.sequence().id();
+
+        for drach in drachs.iter() {
+            let label = match self
+                .labels
+                .get(&(id.to_string(), drach.start(), drach.strand()))
+            {
+                Some(label) => *label,
+                None => continue,
+            };
+
+            if let Some((_, values)) = self.sites.encode(drach, ctx)? {
+                self.samples.push(values);
+                self.targets.push(label);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number of labelled sites found so far.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Number of modified sites found so far.
+    pub fn positives(&self) -> usize {
+        self.targets.iter().filter(|target| **target).count()
+    }
+
+    pub fn train(&self, params: &ForestParams) -> Result<RandomForest> {
+        if self.is_empty() {
+            let msg = "None of the labelled sites were found in the sequences";
+            return Err(Box::new(Error::new(msg)));
+        }
+
+        RandomForest::train(&self.samples, &self.targets, params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod read_labels {
+        use std::io::Cursor;
+
+        use crate::domain::entities::drach::Strand;
+        use crate::domain::usecases::train_forest::read_labels;
+
+        #[test]
+        fn should_read_labels_skipping_header_and_comments() {
+            let input = "id\tstart\tlabel\n# comment\nseq1\t2\t1\n\nseq1\t9\tfalse\n";
+            let labels = read_labels(Cursor::new(input)).unwrap();
+
+            assert_eq!(labels.len(), 2);
+            assert!(labels[&(String::from("seq1"), 2, Strand::Forward)]);
+            assert!(!labels[&(String::from("seq1"), 9, Strand::Forward)]);
+        }
+
+        #[test]
+        fn should_label_each_strand_separately() {
+            let input = "seq1\t2\t1\t+\nseq1\t2\t0\t-\n";
+            let labels = read_labels(Cursor::new(input)).unwrap();
+
+            assert!(labels[&(String::from("seq1"), 2, Strand::Forward)]);
+            assert!(!labels[&(String::from("seq1"), 2, Strand::Reverse)]);
+        }
+
+        #[test]
+        fn should_reject_malformed_lines() {
+            let res = read_labels(Cursor::new("seq1\t2\tmaybe\n"));
+
+            assert_eq!(
+                res.err().unwrap().to_string(),
+                "Invalid label on line 1: 'seq1\t2\tmaybe', expected id, start, 0 or 1 and an \
+                 optional strand"
+            );
+        }
+    }
+
+    mod train_forest {
+        use crate::domain::entities::drach::{PaddingPolicy, Strand};
+        use crate::domain::entities::forest::ForestParams;
+        use crate::domain::usecases::encode_features::{EncodeFeatures, SiteEncoder};
+        use crate::domain::usecases::fixtures::Scanned;
+        use crate::domain::usecases::train_forest::{SiteLabels, TrainForest};
+
+        #[test]
+        fn should_train_on_labelled_sites_only() {
+            let scanned = Scanned::new("seq1", "CCGGACUUUAAGGACAAAA");
+            let drachs = &scanned.drachs;
+            let sites = SiteEncoder::new(
+                EncodeFeatures::parse("onehot").unwrap(),
+                2,
+                2,
+                PaddingPolicy::Pad('N'),
+                0,
+            );
+            let labels = SiteLabels::from([((String::from("seq1"), 2, Strand::Forward), true)]);
+
+            let mut train = TrainForest::new(sites, labels);
+            train.add(drachs, &scanned.context()).unwrap();
+
+            assert_eq!(drachs.len(), 2);
+            assert_eq!(train.len(), 1);
+            assert_eq!(train.positives(), 1);
+            assert!(train.train(&ForestParams::default()).is_ok());
+        }
+    }
+}
@@ -1,82 +1,253 @@
-use std::fs::File;
 use std::io::Write;
 
 use crate::domain::entities::{
-    drach::{DrachContext, DrachNeighbor, DrachNeighborPosition},
+    drach::{DrachContext, DrachNeighbor, DrachNeighborPosition, MaskedBase, PaddingPolicy},
     Drach,
 };
+use crate::domain::usecases::header_template::HeaderTemplate;
+use crate::domain::usecases::records::{JsonStream, NeighborRecord, Provenance};
+use crate::utils::write_row;
 use crate::Result;
 
 pub(crate) trait WriteStrategy {
-    fn write(&self, file: &File, neighbor: DrachNeighbor) -> Result;
+    /// Write anything that must precede the neighbors, such as a header.
+    fn begin(&self, _out: &mut dyn Write) -> Result {
+        Ok(())
+    }
+
+    fn write(&self, out: &mut dyn Write, neighbor: DrachNeighbor) -> Result;
+
+    /// Write anything that must follow the neighbors.
+    fn end(&self, _out: &mut dyn Write) -> Result {
+        Ok(())
+    }
 }
 
 pub(crate) struct WriteDrachNeighbor<'a> {
-    file: &'a File,
+    out: &'a mut dyn Write,
     write_strategy: Box<dyn WriteStrategy>,
+    left_length: usize,
+    right_length: usize,
+    padding: PaddingPolicy,
+    seed: u64,
 }
 
 impl<'a> WriteDrachNeighbor<'a> {
-    pub fn new(file: &'a File, write_strategy: Box<dyn WriteStrategy>) -> Self {
+    pub fn new(
+        out: &'a mut dyn Write,
+        write_strategy: Box<dyn WriteStrategy>,
+        left_length: usize,
+        right_length: usize,
+        padding: PaddingPolicy,
+        seed: u64,
+    ) -> Self {
         Self {
-            file,
+            out,
             write_strategy,
+            left_length,
+            right_length,
+            padding,
+            seed,
         }
     }
 
+    pub fn begin(&mut self) -> Result {
+        self.write_strategy.begin(self.out)
+    }
+
     pub fn write(
         &mut self,
         drach: &Drach,
         ctx: &DrachContext,
         position: DrachNeighborPosition,
-        length: usize,
     ) -> Result {
         let mut builder = DrachNeighbor::builder();
         let neighbor = builder
             .set_drach(drach)
             .set_context(ctx.clone())
             .set_position(position)
-            .set_length(length)
-            .build()
-            .unwrap();
+            .set_left_length(self.left_length)
+            .set_right_length(self.right_length)
+            .set_padding(self.padding)
+            .set_seed(self.seed)
+            .build()?;
 
-        self.write_strategy.write(self.file, neighbor)
+        if neighbor.is_skipped() {
+            return Ok(());
+        }
+
+        self.write_strategy.write(self.out, neighbor)
+    }
+
+    pub fn end(&mut self) -> Result {
+        self.write_strategy.end(self.out)
     }
 }
 
-pub(crate) struct BasicWriteStrategy;
+/// Writes each neighbor as a FASTA record with a header built from a
+/// [`HeaderTemplate`].
+pub(crate) struct BasicWriteStrategy {
+    header: HeaderTemplate,
+}
+
+impl BasicWriteStrategy {
+    pub fn new(header: HeaderTemplate) -> Self {
+        Self { header }
+    }
+}
 
 impl WriteStrategy for BasicWriteStrategy {
-    fn write(&self, file: &File, neighbor: DrachNeighbor) -> Result {
-        let mut file = file;
-        write!(file, "{}", neighbor)?;
+    fn write(&self, out: &mut dyn Write, neighbor: DrachNeighbor) -> Result {
+        let (sequence, masked) = neighbor.render()?;
+        let header = self.header.render(&neighbor, &sequence, &masked);
+
+        writeln!(out, ">{}\n{}", header, sequence)?;
         Ok(())
     }
 }
 
-pub(crate) struct VerboseWriteStrategy;
+/// Writes one delimited row per drach, with both of its flanks, under a
+/// header row. Neighbors are expected at the window position.
+pub(crate) struct TableWriteStrategy {
+    delimiter: char,
+}
+
+impl TableWriteStrategy {
+    pub const COLUMNS: [&'static str; 11] = [
+        "id",
+        "index",
+        "start",
+        "end",
+        "strand",
+        "motif",
+        "left_flank",
+        "right_flank",
+        "masked",
+        "left_masked_bases",
+        "right_masked_bases",
+    ];
+
+    pub fn new(delimiter: char) -> Self {
+        Self { delimiter }
+    }
+}
+
+impl WriteStrategy for TableWriteStrategy {
+    fn begin(&self, out: &mut dyn Write) -> Result {
+        let header = Self::COLUMNS.map(String::from);
+        write_row(out, &header, self.delimiter)
+    }
 
-impl WriteStrategy for VerboseWriteStrategy {
-    fn write(&self, file: &File, neighbor: DrachNeighbor) -> Result {
-        let mut file = file;
+    fn write(&self, out: &mut dyn Write, neighbor: DrachNeighbor) -> Result {
         let drach = neighbor.drach();
-        writeln!(file, "{}", drach.payload())?;
-        writeln!(
-            file,
-            "{} em {}-{}",
-            drach.index() + 1,
-            drach.start() + 1,
-            drach.end()
-        )?;
-        writeln!(
-            file,
-            "{}: {}\n",
-            match neighbor.position() {
-                DrachNeighborPosition::Left => "Anterior",
-                DrachNeighborPosition::Right => "Posterior",
-            },
-            neighbor
-        )?;
-        Ok(())
+        let (left, left_masked) = neighbor
+            .with_position(DrachNeighborPosition::Left)
+            .render()?;
+        let (right, right_masked) = neighbor
+            .with_position(DrachNeighborPosition::Right)
+            .render()?;
+
+        let fields = [
+            neighbor.context().sequence().id().to_string(),
+            drach.index().to_string(),
+            drach.start().to_string(),
+            drach.end().to_string(),
+            drach.strand().symbol().to_string(),
+            drach.payload().to_string(),
+            left,
+            right,
+            (left_masked.len() + right_masked.len()).to_string(),
+            MaskedBase::format_list(&left_masked),
+            MaskedBase::format_list(&right_masked),
+        ];
+
+        write_row(out, &fields, self.delimiter)
+    }
+}
+
+/// One [`NeighborRecord`] per neighbor, as a JSON array or as JSON Lines.
+pub(crate) struct JsonWriteStrategy {
+    stream: JsonStream,
+    provenance: Provenance,
+}
+
+impl JsonWriteStrategy {
+    pub fn new(lines: bool, provenance: Provenance) -> Self {
+        Self {
+            stream: JsonStream::new(lines),
+            provenance,
+        }
+    }
+}
+
+impl WriteStrategy for JsonWriteStrategy {
+    fn begin(&self, out: &mut dyn Write) -> Result {
+        self.stream.begin(out)
+    }
+
+    fn write(&self, out: &mut dyn Write, neighbor: DrachNeighbor) -> Result {
+        let record = NeighborRecord::new(&neighbor, &self.provenance)?;
+        self.stream.write(out, &record)
+    }
+
+    fn end(&self, out: &mut dyn Write) -> Result {
+        self.stream.end(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod table_write_strategy {
+        use crate::domain::entities::drach::{DrachNeighborPosition, PaddingPolicy};
+        use crate::domain::usecases::fixtures::{Scanned, PAYLOAD};
+        use crate::domain::usecases::write_drach_neighbor::{
+            TableWriteStrategy, WriteDrachNeighbor,
+        };
+        use crate::Result;
+
+        fn write_table(id: &str, payload: &str, delimiter: char) -> Result<String> {
+            let scanned = Scanned::new(id, payload);
+            let ctx = scanned.context();
+            let mut out = vec![];
+
+            let mut writer = WriteDrachNeighbor::new(
+                &mut out,
+                Box::new(TableWriteStrategy::new(delimiter)),
+                3,
+                2,
+                PaddingPolicy::Pad('N'),
+                0,
+            );
+
+            writer.begin()?;
+            for drach in scanned.drachs.iter() {
+                writer.write(drach, &ctx, DrachNeighborPosition::Window)?;
+            }
+
+            Ok(String::from_utf8(out)?)
+        }
+
+        #[test]
+        fn should_write_one_row_per_drach() -> Result {
+            let table = write_table("seq1", PAYLOAD, '\t')?;
+            let expect = concat!(
+                "id\tindex\tstart\tend\tstrand\tmotif\tleft_flank\tright_flank\tmasked\t",
+                "left_masked_bases\tright_masked_bases\n",
+                "seq1\t0\t2\t7\t+\tGGACU\tNCC\tUU\t0\t-\t-\n",
+            );
+
+            assert_eq!(table, expect);
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_quote_fields_containing_the_delimiter() -> Result {
+            let table = write_table("seq,1", PAYLOAD, ',')?;
+
+            assert!(table.ends_with("\"seq,1\",0,2,7,+,GGACU,NCC,UU,0,-,-\n"));
+
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,225 @@
+use std::io::Write;
+
+use crate::domain::entities::drach::{DrachContext, DrachNeighbor};
+use crate::domain::entities::{Drach, Motif};
+use crate::domain::usecases::encode_features::SiteEncoder;
+use crate::utils::npy;
+use crate::utils::write_row;
+use crate::Result;
+
+/// Columns linking each row of features back to its site.
+pub const INDEX_COLUMNS: [&str; 6] = ["id", "index", "start", "end", "strand", "motif"];
+
+/// How the matrix of features is written.
+pub(crate) trait MatrixFormat {
+    /// Write anything that must precede the rows, such as a header.
+    fn begin(&mut self, _out: &mut dyn Write, _columns: &[String]) -> Result {
+        Ok(())
+    }
+
+    /// Write the features of the window around `neighbor`'s drach.
+    fn write(&mut self, out: &mut dyn Write, neighbor: &DrachNeighbor, values: &[f64]) -> Result;
+
+    /// Write anything that must follow the rows.
+    fn end(&mut self, _out: &mut dyn Write) -> Result {
+        Ok(())
+    }
+}
+
+pub(crate) struct WriteFeatures<'a> {
+    out: &'a mut dyn Write,
+    format: Box<dyn MatrixFormat + 'a>,
+    sites: SiteEncoder,
+    rows: usize,
+    columns: usize,
+}
+
+impl<'a> WriteFeatures<'a> {
+    pub fn new(
+        out: &'a mut dyn Write,
+        format: Box<dyn MatrixFormat + 'a>,
+        sites: SiteEncoder,
+    ) -> Self {
+        Self {
+            out,
+            format,
+            sites,
+            rows: 0,
+            columns: 0,
+        }
+    }
+
+    /// Write the header of windows around sites of `motif`.
+    pub fn begin(&mut self, motif: &Motif) -> Result {
+        let columns = self.sites.columns(motif);
+        self.columns = columns.len();
+        self.format.begin(self.out, &columns)
+    }
+
+    pub fn write(&mut self, drach: &Drach, ctx: &DrachContext) -> Result {
+        match self.sites.encode(drach, ctx)? {
+            Some((neighbor, values)) => {
+                self.rows += 1;
+                self.format.write(self.out, &neighbor, &values)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Number of sites written so far and of features of each.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    pub fn end(&mut self) -> Result {
+        self.format.end(self.out)
+    }
+}
+
+/// Fields of [`INDEX_COLUMNS`] for the site of `neighbor`.
+fn index_fields(neighbor: &DrachNeighbor) -> Vec<String> {
+    let drach = neighbor.drach();
+
+    vec![
+        neighbor.context().sequence().id().to_string(),
+        drach.index().to_string(),
+        drach.start().to_string(),
+        drach.end().to_string(),
+        drach.strand().symbol().to_string(),
+        drach.payload().to_string(),
+    ]
+}
+
+/// One delimited row per site, starting with the [`INDEX_COLUMNS`].
+pub(crate) struct TableMatrixFormat {
+    delimiter: char,
+}
+
+impl TableMatrixFormat {
+    pub fn new(delimiter: char) -> Self {
+        Self { delimiter }
+    }
+}
+
+impl MatrixFormat for TableMatrixFormat {
+    fn begin(&mut self, out: &mut dyn Write, columns: &[String]) -> Result {
+        let mut header: Vec<String> = INDEX_COLUMNS.map(String::from).to_vec();
+        header.extend_from_slice(columns);
+        write_row(out, &header, self.delimiter)
+    }
+
+    fn write(&mut self, out: &mut dyn Write, neighbor: &DrachNeighbor, values: &[f64]) -> Result {
+        let mut fields = index_fields(neighbor);
+        fields.extend(values.iter().map(f64::to_string));
+        write_row(out, &fields, self.delimiter)
+    }
+}
+
+/// A NumPy `.npy` array of the features, whose rows are streamed after a
+/// header giving no rows. Once every row is written, the header must be
+/// patched with [`npy::patch_shape`], so the array needs a seekable,
+/// uncompressed output. The sites of its rows are written to a separate tab
+/// separated index, whose `row` column is the 0-based row of the array.
+pub(crate) struct NpyMatrixFormat<'a> {
+    index: &'a mut dyn Write,
+    rows: usize,
+}
+
+impl<'a> NpyMatrixFormat<'a> {
+    pub fn new(index: &'a mut dyn Write) -> Self {
+        Self { index, rows: 0 }
+    }
+}
+
+impl MatrixFormat for NpyMatrixFormat<'_> {
+    fn begin(&mut self, out: &mut dyn Write, columns: &[String]) -> Result {
+        npy::write_header(out, (0, columns.len()))?;
+        writeln!(self.index, "row\t{}", INDEX_COLUMNS.join("\t"))?;
+        Ok(())
+    }
+
+    fn write(&mut self, out: &mut dyn Write, neighbor: &DrachNeighbor, values: &[f64]) -> Result {
+        let mut fields = vec![self.rows.to_string()];
+        fields.extend(index_fields(neighbor));
+        write_row(self.index, &fields, '\t')?;
+        npy::write_row(out, values)?;
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod write_features {
+        use std::io::{Cursor, Write};
+
+        use crate::domain::entities::drach::PaddingPolicy;
+        use crate::domain::usecases::encode_features::{EncodeFeatures, SiteEncoder};
+        use crate::domain::usecases::fixtures::Scanned;
+        use crate::domain::usecases::write_features::{
+            MatrixFormat, NpyMatrixFormat, TableMatrixFormat, WriteFeatures,
+        };
+        use crate::utils::npy;
+        use crate::Result;
+
+        fn write(
+            format: Box<dyn MatrixFormat + '_>,
+            out: &mut dyn Write,
+        ) -> Result<(usize, usize)> {
+            let scanned = Scanned::default();
+            let ctx = scanned.context();
+            let sites = SiteEncoder::new(
+                EncodeFeatures::parse("onehot")?,
+                1,
+                1,
+                PaddingPolicy::Pad('N'),
+                0,
+            );
+            let mut write_features = WriteFeatures::new(out, format, sites);
+
+            write_features.begin(&scanned.motif)?;
+            for drach in scanned.drachs.iter() {
+                write_features.write(drach, &ctx)?;
+            }
+            write_features.end()?;
+            Ok(write_features.shape())
+        }
+
+        #[test]
+        fn should_write_one_row_per_site_with_its_index() -> Result {
+            let mut out = vec![];
+            write(Box::new(TableMatrixFormat::new('\t')), &mut out)?;
+
+            let table = String::from_utf8(out)?;
+            let lines: Vec<&str> = table.lines().collect();
+
+            assert_eq!(lines.len(), 2);
+            assert!(lines[0].starts_with("id\tindex\tstart\tend\tstrand\tmotif\tonehot_1_A\t"));
+            assert_eq!(lines[0].split('\t').count(), 6 + 7 * 4);
+            assert!(lines[1].starts_with("seq1\t0\t2\t7\t+\tGGACU\t0\t1\t0\t0\t0\t0\t1\t0\t"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_write_an_array_and_a_separate_index() -> Result {
+            let mut out = Cursor::new(vec![]);
+            let mut index = vec![];
+            let shape = write(Box::new(NpyMatrixFormat::new(&mut index)), &mut out)?;
+            npy::patch_shape(&mut out, shape)?;
+            let out = out.into_inner();
+
+            let header_length = u16::from_le_bytes([out[8], out[9]]) as usize;
+            let header = std::str::from_utf8(&out[10..10 + header_length])?;
+
+            assert!(header.contains("'shape': (1, 28)"));
+            assert_eq!(out.len(), 10 + header_length + 28 * 8);
+            assert_eq!(
+                String::from_utf8(index)?,
+                "row\tid\tindex\tstart\tend\tstrand\tmotif\n0\tseq1\t0\t2\t7\t+\tGGACU\n"
+            );
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::io::Write;
+
+use crate::domain::entities::drach::{
+    DrachContext, DrachNeighbor, DrachNeighborPosition, MaskedBase, PaddingPolicy,
+};
+use crate::Result;
+
+/// Language of the human readable report.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReportLang {
+    #[default]
+    En,
+    Pt,
+}
+
+impl ReportLang {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "en" => Some(ReportLang::En),
+            "pt" => Some(ReportLang::Pt),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> &'static ReportLabels {
+        match self {
+            ReportLang::En => &EN_LABELS,
+            ReportLang::Pt => &PT_LABELS,
+        }
+    }
+}
+
+struct ReportLabels {
+    sequence: &'static str,
+    sites: &'static str,
+    columns: [&'static str; 9],
+}
+
+const EN_LABELS: ReportLabels = ReportLabels {
+    sequence: "Sequence",
+    sites: "sites",
+    columns: [
+        "site",
+        "motif",
+        "start",
+        "end",
+        "strand",
+        "left flank",
+        "right flank",
+        "left masked",
+        "right masked",
+    ],
+};
+
+const PT_LABELS: ReportLabels = ReportLabels {
+    sequence: "Sequência",
+    sites: "sítios",
+    columns: [
+        "sítio",
+        "motivo",
+        "início",
+        "fim",
+        "fita",
+        "flanco anterior",
+        "flanco posterior",
+        "mascaradas anterior",
+        "mascaradas posterior",
+    ],
+};
+
+/// Writes a human readable report with one row per site, kept apart from
+/// the sequence outputs. Sites are numbered from 1 and coordinates are
+/// 1-based and closed. Masked bases of each flank are listed as in the
+/// `masked_bases` header field, by their 0-based offset in the flank.
+pub(crate) struct WriteReport<'a> {
+    out: &'a mut dyn Write,
+    lang: ReportLang,
+    left_length: usize,
+    right_length: usize,
+    padding: PaddingPolicy,
+    seed: u64,
+}
+
+impl<'a> WriteReport<'a> {
+    pub fn new(
+        out: &'a mut dyn Write,
+        lang: ReportLang,
+        left_length: usize,
+        right_length: usize,
+        padding: PaddingPolicy,
+        seed: u64,
+    ) -> Self {
+        Self {
+            out,
+            lang,
+            left_length,
+            right_length,
+            padding,
+            seed,
+        }
+    }
+
+    /// Write the section of the sequence in `ctx`. Sites skipped by the
+    /// padding policy are left out of the rows and of the count.
+    pub fn write(&mut self, ctx: &DrachContext) -> Result {
+        let labels = self.lang.labels();
+        let seq = ctx.sequence();
+        let mut rows = vec![];
+
+        for drach in ctx.drachs().iter() {
+            let window = DrachNeighbor::new(
+                drach,
+                ctx.clone(),
+                DrachNeighborPosition::Window,
+                self.left_length,
+                self.right_length,
+                self.padding,
+                self.seed,
+            );
+
+            if window.is_skipped() {
+                continue;
+            }
+
+            let (left, left_masked) = window.with_position(DrachNeighborPosition::Left).render()?;
+            let (right, right_masked) = window
+                .with_position(DrachNeighborPosition::Right)
+                .render()?;
+
+            rows.push(format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                drach.index() + 1,
+                drach.payload(),
+                drach.start() + 1,
+                drach.end(),
+                drach.strand().symbol(),
+                left,
+                right,
+                MaskedBase::format_list(&left_masked),
+                MaskedBase::format_list(&right_masked)
+            ));
+        }
+
+        writeln!(
+            self.out,
+            "{} {}: {} {}",
+            labels.sequence,
+            seq.id(),
+            rows.len(),
+            labels.sites
+        )?;
+
+        if !rows.is_empty() {
+            writeln!(self.out, "{}", labels.columns.join("\t"))?;
+
+            for row in rows.iter() {
+                writeln!(self.out, "{}", row)?;
+            }
+        }
+
+        writeln!(self.out)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod write_report {
+        use crate::domain::entities::drach::PaddingPolicy;
+        use crate::domain::usecases::fixtures::{Scanned, PAYLOAD};
+        use crate::domain::usecases::write_report::{ReportLang, WriteReport};
+        use crate::Result;
+
+        fn report(lang: ReportLang) -> Result<String> {
+            report_of(PAYLOAD, lang, 3, 2, PaddingPolicy::Pad('N'))
+        }
+
+        fn report_of(
+            payload: &str,
+            lang: ReportLang,
+            left: usize,
+            right: usize,
+            padding: PaddingPolicy,
+        ) -> Result<String> {
+            let scanned = Scanned::new("seq1", payload);
+            let mut out = vec![];
+
+            WriteReport::new(&mut out, lang, left, right, padding, 0).write(&scanned.context())?;
+
+            Ok(String::from_utf8(out)?)
+        }
+
+        #[test]
+        fn should_use_1_based_coordinates() -> Result {
+            let expect = concat!(
+                "Sequence seq1: 1 sites\n",
+                "site\tmotif\tstart\tend\tstrand\tleft flank\tright flank\tleft masked\t\
+                 right masked\n",
+                "1\tGGACU\t3\t7\t+\tNCC\tUU\t-\t-\n",
+                "\n",
+            );
+
+            assert_eq!(report(ReportLang::En)?, expect);
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_list_the_masked_bases_of_each_flank() -> Result {
+            let text = report_of(
+                "GGACUAGGACUAAAAA",
+                ReportLang::En,
+                6,
+                6,
+                PaddingPolicy::Pad('N'),
+            )?;
+            let first: Vec<&str> = text.lines().nth(2).unwrap().split('\t').collect();
+
+            assert_eq!(first[7], "-");
+            assert!(first[8]
+                .split(',')
+                .all(|base| (1..6).contains(&base[..1].parse::<usize>().unwrap())));
+            assert!(!first[6].contains("GGACU"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_count_only_the_sites_not_skipped() -> Result {
+            let partly = report_of(
+                "CCGGACUUUUUGGACUUU",
+                ReportLang::En,
+                3,
+                2,
+                PaddingPolicy::Skip,
+            )?;
+            let fully = report_of(PAYLOAD, ReportLang::En, 3, 2, PaddingPolicy::Skip)?;
+
+            assert_eq!(
+                partly,
+                concat!(
+                    "Sequence seq1: 1 sites\n",
+                    "site\tmotif\tstart\tend\tstrand\tleft flank\tright flank\tleft masked\t\
+                     right masked\n",
+                    "2\tGGACU\t12\t16\t+\tUUU\tUU\t-\t-\n",
+                    "\n",
+                )
+            );
+            assert_eq!(fully, "Sequence seq1: 0 sites\n\n");
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_translate_labels() -> Result {
+            let text = report(ReportLang::Pt)?;
+
+            assert!(text.starts_with("Sequência seq1: 1 sítios\nsítio\tmotivo\tinício\tfim"));
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::Write;
+
+use crate::domain::entities::{Drach, Motif, Sequence};
+use crate::domain::usecases::records::{JsonStream, Provenance, SiteRecord};
+use crate::utils::write_row;
+use crate::{cli::NAME, Result};
+
+/// How the sites found in a sequence are written.
+pub(crate) trait SiteFormat {
+    /// Write anything that must precede the sites, such as a header.
+    fn begin(&self, _out: &mut dyn Write) -> Result {
+        Ok(())
+    }
+
+    /// Write anything that must precede the sites of `seq`.
+    fn begin_sequence(&self, _out: &mut dyn Write, _seq: &Sequence) -> Result {
+        Ok(())
+    }
+
+    fn write(&self, out: &mut dyn Write, seq: &Sequence, drach: &Drach) -> Result;
+
+    /// Write anything that must follow the sites.
+    fn end(&self, _out: &mut dyn Write) -> Result {
+        Ok(())
+    }
+}
+
+pub(crate) struct WriteSites<'a> {
+    out: &'a mut dyn Write,
+    format: Box<dyn SiteFormat>,
+}
+
+impl<'a> WriteSites<'a> {
+    pub fn new(out: &'a mut dyn Write, format: Box<dyn SiteFormat>) -> Self {
+        Self { out, format }
+    }
+
+    pub fn begin(&mut self) -> Result {
+        self.format.begin(self.out)
+    }
+
+    /// Write all `drachs` found in `seq`.
+    pub fn write(&mut self, seq: &Sequence, drachs: &[Drach]) -> Result {
+        self.format.begin_sequence(self.out, seq)?;
+
+        for drach in drachs.iter() {
+            self.format.write(self.out, seq, drach)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn end(&mut self) -> Result {
+        self.format.end(self.out)
+    }
+}
+
+/// One delimited row per site under a header row. Coordinates are 0-based
+/// and half-open.
+pub(crate) struct TableSiteFormat {
+    delimiter: char,
+}
+
+impl TableSiteFormat {
+    pub const COLUMNS: [&'static str; 6] = ["id", "index", "start", "end", "strand", "motif"];
+
+    pub fn new(delimiter: char) -> Self {
+        Self { delimiter }
+    }
+}
+
+impl SiteFormat for TableSiteFormat {
+    fn begin(&self, out: &mut dyn Write) -> Result {
+        let header = Self::COLUMNS.map(String::from);
+        write_row(out, &header, self.delimiter)
+    }
+
+    fn write(&self, out: &mut dyn Write, seq: &Sequence, drach: &Drach) -> Result {
+        let fields = [
+            seq.id().to_string(),
+            drach.index().to_string(),
+            drach.start().to_string(),
+            drach.end().to_string(),
+            drach.strand().symbol().to_string(),
+            drach.payload().to_string(),
+        ];
+
+        write_row(out, &fields, self.delimiter)
+    }
+}
+
+/// BED6, 0-based and half-open, naming each site after its motif and strand
+/// so they stay distinguishable once intersected.
+pub(crate) struct BedSiteFormat;
+
+impl SiteFormat for BedSiteFormat {
+    fn write(&self, out: &mut dyn Write, seq: &Sequence, drach: &Drach) -> Result {
+        let strand = drach.strand().symbol();
+
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}({})\t0\t{}",
+            seq.id(),
+            drach.start(),
+            drach.end(),
+            drach.payload(),
+            strand,
+            strand
+        )?;
+        Ok(())
+    }
+}
+
+/// GFF3, 1-based and closed, with a sequence region per sequence id.
+pub(crate) struct Gff3SiteFormat {
+    motif_name: String,
+    /// Ids whose sequence region was written, since GFF3 allows only one
+    /// per id and records may share an id.
+    regions: RefCell<HashSet<String>>,
+}
+
+impl Gff3SiteFormat {
+    /// Sequence Ontology type of the written features.
+    pub const FEATURE_TYPE: &'static str = "sequence_motif";
+
+    pub fn new(motif: &Motif) -> Self {
+        Self {
+            motif_name: String::from(motif.name()),
+            regions: RefCell::new(HashSet::new()),
+        }
+    }
+}
+
+impl SiteFormat for Gff3SiteFormat {
+    fn begin(&self, out: &mut dyn Write) -> Result {
+        writeln!(out, "##gff-version 3")?;
+        Ok(())
+    }
+
+    fn begin_sequence(&self, out: &mut dyn Write, seq: &Sequence) -> Result {
+        if !seq.payload().is_empty() && self.regions.borrow_mut().insert(seq.id().to_string()) {
+            let id = escape_gff3(seq.id(), is_seqid_char);
+            writeln!(out, "##sequence-region {} 1 {}", id, seq.payload().len())?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&self, out: &mut dyn Write, seq: &Sequence, drach: &Drach) -> Result {
+        let id = format!("{}_{}", seq.id(), drach.index());
+
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t.\t{}\t.\tID={};Name={};sequence={}",
+            escape_gff3(seq.id(), is_seqid_char),
+            NAME,
+            Self::FEATURE_TYPE,
+            drach.start() + 1,
+            drach.end(),
+            drach.strand().symbol(),
+            escape_gff3(&id, is_attribute_char),
+            escape_gff3(&self.motif_name, is_attribute_char),
+            drach.payload()
+        )?;
+        Ok(())
+    }
+}
+
+/// One [`SiteRecord`] per site, as a JSON array or as JSON Lines.
+pub(crate) struct JsonSiteFormat {
+    stream: JsonStream,
+    provenance: Provenance,
+}
+
+impl JsonSiteFormat {
+    pub fn new(lines: bool, provenance: Provenance) -> Self {
+        Self {
+            stream: JsonStream::new(lines),
+            provenance,
+        }
+    }
+}
+
+impl SiteFormat for JsonSiteFormat {
+    fn begin(&self, out: &mut dyn Write) -> Result {
+        self.stream.begin(out)
+    }
+
+    fn write(&self, out: &mut dyn Write, seq: &Sequence, drach: &Drach) -> Result {
+        let record = SiteRecord::new(seq, drach, &self.provenance);
+        self.stream.write(out, &record)
+    }
+
+    fn end(&self, out: &mut dyn Write) -> Result {
+        self.stream.end(out)
+    }
+}
+
+/// Characters allowed unescaped in a GFF3 seqid.
+fn is_seqid_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ".:^*$@!+_?-|".contains(c)
+}
+
+/// Characters allowed unescaped in a GFF3 attribute value.
+fn is_attribute_char(c: char) -> bool {
+    !c.is_control() && !";=&,%".contains(c)
+}
+
+/// Percent-encode the characters of `text` that are not `allowed`.
+fn escape_gff3(text: &str, allowed: fn(char) -> bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if allowed(c) {
+            escaped.push(c);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    mod write_sites {
+        use crate::domain::entities::{Motif, Sequence};
+        use crate::domain::usecases::scan_drachs::{ScanDrachs, ScanMode, StrandMode};
+        use crate::domain::usecases::write_sites::{
+            BedSiteFormat, Gff3SiteFormat, SiteFormat, WriteSites,
+        };
+        use crate::Result;
+
+        fn write(format: Box<dyn SiteFormat>, id: &str) -> Result<String> {
+            let motif = Motif::default();
+            let scan_drachs = ScanDrachs::new(&motif, ScanMode::default(), StrandMode::Both);
+            let seq = scan_drachs.prepare(Sequence::new(id, id, "CCGGACUUAGUCCAA", None));
+            let drachs = scan_drachs.scan(&seq);
+            let mut out = vec![];
+
+            let mut writer = WriteSites::new(&mut out, format);
+            writer.begin()?;
+            writer.write(&seq, &drachs)?;
+
+            Ok(String::from_utf8(out)?)
+        }
+
+        #[test]
+        fn should_write_bed6() -> Result {
+            let bed = write(Box::new(BedSiteFormat), "chr1")?;
+            let expect = concat!(
+                "chr1\t2\t7\tGGACU(+)\t0\t+\n",
+                "chr1\t8\t13\tGGACU(-)\t0\t-\n",
+            );
+
+            assert_eq!(bed, expect);
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_write_gff3_with_1_based_coordinates() -> Result {
+            let gff = write(Box::new(Gff3SiteFormat::new(&Motif::default())), "chr 1")?;
+            let expect = concat!(
+                "##gff-version 3\n",
+                "##sequence-region chr%201 1 15\n",
+                "chr%201\trustsramp\tsequence_motif\t3\t7\t.\t+\t.\tID=chr 1_0;Name=DRACH;sequence=GGACU\n",
+                "chr%201\trustsramp\tsequence_motif\t9\t13\t.\t-\t.\tID=chr 1_1;Name=DRACH;sequence=GGACU\n",
+            );
+
+            assert_eq!(gff, expect);
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_write_one_gff3_region_per_id() -> Result {
+            let seq = Sequence::new("chr1", "chr1 a", "CCGGACUU", None);
+            let other = Sequence::new("chr1", "chr1 b", "GGACU", None);
+            let mut out = vec![];
+
+            let mut writer =
+                WriteSites::new(&mut out, Box::new(Gff3SiteFormat::new(&Motif::default())));
+            writer.begin()?;
+            writer.write(&seq, &[])?;
+            writer.write(&other, &[])?;
+
+            assert_eq!(
+                String::from_utf8(out)?,
+                "##gff-version 3\n##sequence-region chr1 1 8\n"
+            );
+
+            Ok(())
+        }
+    }
+}
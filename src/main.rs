@@ -7,6 +7,7 @@ fn main() {
         Ok(val) => val,
         Err(err) => {
             eprintln!("Cli error: {}", err);
+            eprintln!("Run with --help for usage.");
             process::exit(1);
         }
     };
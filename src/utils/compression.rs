@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::Result;
+
+/// First bytes of every gzip member, bgzip blocks included.
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+pub const GZIP_EXT: &str = ".gz";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+}
+
+impl Compression {
+    /// Compression implied by the extension of `path`.
+    pub fn from_path(path: &str) -> Self {
+        if path.ends_with(GZIP_EXT) {
+            Compression::Gzip
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Extension appended to files written with this compression.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => GZIP_EXT,
+        }
+    }
+}
+
+/// Wrap `reader`, transparently decompressing it when it starts with the
+/// gzip magic bytes. Concatenated gzip members, as written by bgzip, are
+/// read as a single stream.
+pub fn decompress<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+    let is_gzip = reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+
+    if is_gzip {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Open `path` for reading, decompressing it if needed.
+pub fn open(path: &str) -> Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    Ok(decompress(BufReader::new(file))?)
+}
+
+/// A writer that optionally gzips what is written to it. [`finish`] must be
+/// called to write the gzip trailer and flush the inner writer.
+///
+/// [`finish`]: CompressedWriter::finish
+pub enum CompressedWriter<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+    pub fn new(inner: W, compression: Compression) -> Self {
+        match compression {
+            Compression::None => CompressedWriter::Plain(inner),
+            Compression::Gzip => {
+                CompressedWriter::Gzip(GzEncoder::new(inner, flate2::Compression::default()))
+            }
+        }
+    }
+
+    pub fn finish(self) -> io::Result<W> {
+        let mut inner = match self {
+            CompressedWriter::Plain(inner) => inner,
+            CompressedWriter::Gzip(encoder) => encoder.finish()?,
+        };
+
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressedWriter::Plain(inner) => inner.write(buf),
+            CompressedWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressedWriter::Plain(inner) => inner.flush(),
+            CompressedWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl<W: Write + Seek> Seek for CompressedWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            CompressedWriter::Plain(inner) => inner.seek(pos),
+            CompressedWriter::Gzip(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "cannot seek in a gzip stream",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod decompress {
+        use std::io::{Cursor, Read, Write};
+
+        use crate::utils::compression::{decompress, CompressedWriter, Compression};
+        use crate::Result;
+
+        #[test]
+        fn should_detect_gzip_by_magic_bytes() -> Result {
+            let mut writer = CompressedWriter::new(vec![], Compression::Gzip);
+            write!(writer, ">id\nACGU\n")?;
+            let gzipped = writer.finish()?;
+
+            let mut text = String::new();
+            decompress(Cursor::new(gzipped))?.read_to_string(&mut text)?;
+
+            assert_eq!(text, ">id\nACGU\n");
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_read_concatenated_members() -> Result {
+            let mut gzipped = vec![];
+
+            for record in [">a\nAC\n", ">b\nGU\n"] {
+                let mut writer = CompressedWriter::new(vec![], Compression::Gzip);
+                write!(writer, "{}", record)?;
+                gzipped.extend(writer.finish()?);
+            }
+
+            let mut text = String::new();
+            decompress(Cursor::new(gzipped))?.read_to_string(&mut text)?;
+
+            assert_eq!(text, ">a\nAC\n>b\nGU\n");
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_pass_plain_text_through() -> Result {
+            let mut text = String::new();
+            decompress(Cursor::new(">id\nACGU\n"))?.read_to_string(&mut text)?;
+
+            assert_eq!(text, ">id\nACGU\n");
+
+            Ok(())
+        }
+    }
+}
@@ -1,3 +1,96 @@
+pub mod compression;
+pub mod npy;
+pub mod output;
+
+use std::io::Write;
+
+use crate::Result;
+
+/// Make `name` safe to use as a file name, replacing anything other than
+/// ASCII letters, digits, `.`, `_` and `-` with `_`.
+pub fn sanitize_filename(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+
+    match name.trim_start_matches('.') {
+        "" => String::from("unnamed"),
+        _ => name,
+    }
+}
+
+/// Derive a seed for `key` from `seed`, so independent random streams can be
+/// drawn from a single user given seed. The derivation is stable across
+/// platforms and releases.
+pub fn derive_seed(seed: u64, key: &str) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    seed.to_le_bytes()
+        .iter()
+        .chain(key.as_bytes())
+        .fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// Quote `field` for a delimited table when it contains the delimiter,
+/// quotes or line breaks.
+pub fn escape_field(field: &str, delimiter: char) -> String {
+    let needs_quotes = field.contains([delimiter, '"', '\n', '\r']);
+
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// Write `fields` as one line of a table separated by `delimiter`, escaping
+/// each field with [`escape_field`].
+pub fn write_row(out: &mut dyn Write, fields: &[String], delimiter: char) -> Result {
+    let row: Vec<String> = fields
+        .iter()
+        .map(|field| escape_field(field, delimiter))
+        .collect();
+
+    writeln!(out, "{}", row.join(&delimiter.to_string()))?;
+    Ok(())
+}
+
 pub fn ends_with_any(string: &str, ends: Vec<&str>) -> bool {
     ends.into_iter().any(|end| string.ends_with(end))
 }
+
+#[cfg(test)]
+mod tests {
+    mod sanitize_filename {
+        use crate::utils::sanitize_filename;
+
+        #[test]
+        fn should_replace_unsafe_characters() {
+            assert_eq!(sanitize_filename("NM_001.1"), "NM_001.1");
+            assert_eq!(sanitize_filename("chr1:10-20/a b"), "chr1_10-20_a_b");
+            assert_eq!(sanitize_filename(".."), "unnamed");
+            assert_eq!(sanitize_filename(""), "unnamed");
+        }
+    }
+
+    mod write_row {
+        use crate::utils::write_row;
+
+        #[test]
+        fn should_escape_and_join_the_fields() {
+            let mut out = Vec::new();
+            let fields = [String::from("a"), String::from("b,c"), String::from("d")];
+
+            write_row(&mut out, &fields, ',').unwrap();
+
+            assert_eq!(String::from_utf8(out).unwrap(), "a,\"b,c\",d\n");
+        }
+    }
+}
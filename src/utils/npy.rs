@@ -0,0 +1,105 @@
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::Result;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Write the header of a version 1.0 NumPy `.npy` array of little endian
+/// `f64` with the given `(rows, columns)` shape. The header is padded as if
+/// the array had the most rows possible, so it can be written before the
+/// rows are counted and patched by [`patch_shape`] once they are.
+pub fn write_header(out: &mut dyn Write, shape: (usize, usize)) -> Result {
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+        shape.0, shape.1
+    );
+
+    // Magic, version and header length take 10 bytes, and the whole
+    // preamble must be a multiple of 64 bytes ending with a newline.
+    let widest = header.len() + usize::MAX.to_string().len() - shape.0.to_string().len();
+    let unpadded = MAGIC.len() + 4 + widest + 1;
+    let length = widest + (64 - unpadded % 64) % 64 + 1;
+    header.push_str(&" ".repeat(length - header.len() - 1));
+    header.push('\n');
+
+    out.write_all(MAGIC)?;
+    out.write_all(&[1, 0])?;
+    out.write_all(&(header.len() as u16).to_le_bytes())?;
+    out.write_all(header.as_bytes())?;
+
+    Ok(())
+}
+
+/// Write one row of the array.
+pub fn write_row(out: &mut dyn Write, values: &[f64]) -> Result {
+    for value in values.iter() {
+        out.write_all(&value.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Rewrite the header at the start of `out` with the final `shape`, and
+/// move back to the end of the array.
+pub fn patch_shape<W: Write + Seek>(out: &mut W, shape: (usize, usize)) -> Result {
+    out.seek(SeekFrom::Start(0))?;
+    write_header(out, shape)?;
+    out.seek(SeekFrom::End(0))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod npy {
+        use std::io::Cursor;
+
+        use crate::utils::npy::{patch_shape, write_header, write_row};
+        use crate::Result;
+
+        #[test]
+        fn should_write_an_aligned_header_and_the_values() -> Result {
+            let mut out = vec![];
+            write_header(&mut out, (2, 2))?;
+            write_row(&mut out, &[1.0, 0.0])?;
+            write_row(&mut out, &[0.25, 0.5])?;
+
+            let header_length = u16::from_le_bytes([out[8], out[9]]) as usize;
+            let header = std::str::from_utf8(&out[10..10 + header_length])?;
+
+            assert_eq!(&out[..8], b"\x93NUMPY\x01\x00");
+            assert_eq!((10 + header_length) % 64, 0);
+            assert!(
+                header.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 2), }")
+            );
+            assert!(header.ends_with(" \n"));
+            assert_eq!(out.len(), 10 + header_length + 4 * 8);
+            assert_eq!(&out[out.len() - 8..], &0.5f64.to_le_bytes());
+
+            Ok(())
+        }
+
+        #[test]
+        fn should_patch_the_shape_in_place() -> Result {
+            let mut out = Cursor::new(vec![]);
+            write_header(&mut out, (0, 1))?;
+            let length = out.get_ref().len();
+
+            for row in 0..1000 {
+                write_row(&mut out, &[row as f64])?;
+            }
+
+            patch_shape(&mut out, (1000, 1))?;
+            write_row(&mut out, &[-1.0])?;
+
+            let out = out.into_inner();
+            let header = std::str::from_utf8(&out[10..length])?;
+
+            assert!(header.contains("'shape': (1000, 1)"));
+            assert_eq!(out.len(), length + 1001 * 8);
+            assert_eq!(&out[length..length + 8], &0f64.to_le_bytes());
+            assert_eq!(&out[out.len() - 8..], &(-1f64).to_le_bytes());
+
+            Ok(())
+        }
+    }
+}
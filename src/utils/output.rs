@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+/// Path given to write to stdout instead of a file.
+pub const STDOUT_PATH: &str = "-";
+
+/// What to do when an output file already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExistingPolicy {
+    #[default]
+    Fail,
+    Overwrite,
+    Skip,
+}
+
+/// A directory receiving one output file per record. Names are made unique
+/// within a run by suffixing repeated ones with `_2`, `_3`, ...
+pub struct OutputDir {
+    path: PathBuf,
+    extension: String,
+    policy: ExistingPolicy,
+    used: HashSet<String>,
+}
+
+impl OutputDir {
+    /// Create the directory at `path`, if needed, for files ending with
+    /// `extension`.
+    pub fn create(path: &str, extension: &str, policy: ExistingPolicy) -> Result<Self> {
+        fs::create_dir_all(path)?;
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            extension: String::from(extension),
+            policy,
+            used: HashSet::new(),
+        })
+    }
+
+    /// Open the file for `name`, or `None` when it already exists and is to
+    /// be skipped.
+    pub fn open(&mut self, name: &str) -> Result<Option<AtomicFile>> {
+        let name = self.unique_name(name);
+        let path = self.path.join(format!("{}{}", name, self.extension));
+
+        if !may_write(&path, self.policy)? {
+            return Ok(None);
+        }
+
+        Ok(Some(AtomicFile::create(path)?))
+    }
+
+    fn unique_name(&mut self, name: &str) -> String {
+        let mut unique = String::from(name);
+        let mut n = 1;
+
+        while self.used.contains(&unique) {
+            n += 1;
+            unique = format!("{}_{}", name, n);
+        }
+
+        self.used.insert(unique.clone());
+        unique
+    }
+}
+
+/// An output receiving whole records, either stdout or a file written
+/// atomically.
+pub enum OutputStream {
+    Stdout(BufWriter<io::Stdout>),
+    Stderr(io::Stderr),
+    /// A device or pipe, such as `/dev/null`, written in place.
+    Special(BufWriter<File>),
+    File(AtomicFile),
+}
+
+impl OutputStream {
+    /// Open `path`, or stdout for [`STDOUT_PATH`]. `None` is returned when
+    /// the file already exists and is to be skipped.
+    pub fn open(path: &str, policy: ExistingPolicy) -> Result<Option<Self>> {
+        if path == STDOUT_PATH {
+            return Ok(Some(OutputStream::Stdout(BufWriter::new(io::stdout()))));
+        }
+
+        let path = PathBuf::from(path);
+
+        if is_special_file(&path) {
+            let file = OpenOptions::new().write(true).open(&path)?;
+            return Ok(Some(OutputStream::Special(BufWriter::new(file))));
+        }
+
+        if !may_write(&path, policy)? {
+            return Ok(None);
+        }
+
+        Ok(Some(OutputStream::File(AtomicFile::create(path)?)))
+    }
+
+    /// Flush what was written, moving files into place.
+    pub fn commit(self) -> io::Result<()> {
+        match self {
+            OutputStream::Stdout(mut stdout) => stdout.flush(),
+            OutputStream::Stderr(mut stderr) => stderr.flush(),
+            OutputStream::Special(mut file) => file.flush(),
+            OutputStream::File(file) => file.commit(),
+        }
+    }
+}
+
+impl Write for OutputStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputStream::Stdout(stdout) => stdout.write(buf),
+            OutputStream::Stderr(stderr) => stderr.write(buf),
+            OutputStream::Special(file) => file.write(buf),
+            OutputStream::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputStream::Stdout(stdout) => stdout.flush(),
+            OutputStream::Stderr(stderr) => stderr.flush(),
+            OutputStream::Special(file) => file.flush(),
+            OutputStream::File(file) => file.flush(),
+        }
+    }
+}
+
+impl Seek for OutputStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            OutputStream::Stdout(_) | OutputStream::Stderr(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "cannot seek in a standard stream",
+            )),
+            OutputStream::Special(file) => file.seek(pos),
+            OutputStream::File(file) => file.seek(pos),
+        }
+    }
+}
+
+/// Whether `path` exists but is not a regular file or directory, so it can
+/// not be replaced by renaming.
+fn is_special_file(path: &Path) -> bool {
+    match fs::metadata(path) {
+        Ok(metadata) => !metadata.is_file() && !metadata.is_dir(),
+        Err(_) => false,
+    }
+}
+
+/// Whether `path` may be written according to `policy`.
+fn may_write(path: &Path, policy: ExistingPolicy) -> Result<bool> {
+    if !path.exists() {
+        return Ok(true);
+    }
+
+    match policy {
+        ExistingPolicy::Overwrite => Ok(true),
+        ExistingPolicy::Skip => Ok(false),
+        ExistingPolicy::Fail => {
+            let msg = format!(
+                "Output file '{}' already exists, use --overwrite or --skip-existing",
+                path.display()
+            );
+            Err(Box::new(Error::new(&msg)))
+        }
+    }
+}
+
+/// A file written to a temporary path next to its destination and renamed
+/// into place by [`commit`]. Dropping it uncommitted removes the temporary
+/// file, so interrupted writes never leave partial outputs behind.
+///
+/// [`commit`]: AtomicFile::commit
+pub struct AtomicFile {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    file: Option<BufWriter<File>>,
+}
+
+impl AtomicFile {
+    pub fn create(path: PathBuf) -> io::Result<Self> {
+        let tmp_path = tmp_path_for(&path);
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+
+        Ok(Self {
+            path,
+            tmp_path,
+            file: Some(BufWriter::new(file)),
+        })
+    }
+
+    /// Get a reference to the atomic file's destination path.
+    pub fn path(&self) -> &Path {
+        self.path.as_ref()
+    }
+
+    /// Flush the written data and move it to the destination path.
+    pub fn commit(mut self) -> io::Result<()> {
+        let file = self.file.take().unwrap();
+        file.into_inner()?.sync_all()?;
+        fs::rename(&self.tmp_path, &self.path)
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().unwrap().flush()
+    }
+}
+
+impl Seek for AtomicFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.as_mut().unwrap().seek(pos)
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap().to_string_lossy();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+#[cfg(test)]
+mod tests {
+    mod output_dir {
+        use std::fs;
+        use std::io::Write;
+
+        use crate::utils::output::{ExistingPolicy, OutputDir};
+        use crate::Result;
+
+        fn write(dir: &mut OutputDir, name: &str, text: &str) -> Result<bool> {
+            match dir.open(name)? {
+                Some(mut file) => {
+                    write!(file, "{}", text)?;
+                    file.commit()?;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+
+        #[test]
+        fn should_suffix_repeated_names() -> Result {
+            let path = "tmp/output_dir_unique";
+            let _ = fs::remove_dir_all(path);
+            let mut dir = OutputDir::create(path, ".fasta", ExistingPolicy::Fail)?;
+
+            write(&mut dir, "seq", "a")?;
+            write(&mut dir, "seq", "b")?;
+            write(&mut dir, "seq", "c")?;
+
+            assert_eq!(fs::read_to_string(format!("{}/seq.fasta", path))?, "a");
+            assert_eq!(fs::read_to_string(format!("{}/seq_2.fasta", path))?, "b");
+            assert_eq!(fs::read_to_string(format!("{}/seq_3.fasta", path))?, "c");
+
+            fs::remove_dir_all(path)?;
+            Ok(())
+        }
+
+        #[test]
+        fn should_apply_existing_policy() -> Result {
+            let path = "tmp/output_dir_policy";
+            let _ = fs::remove_dir_all(path);
+            let file = format!("{}/seq.fasta", path);
+
+            let mut dir = OutputDir::create(path, ".fasta", ExistingPolicy::Fail)?;
+            write(&mut dir, "seq", "first")?;
+
+            let mut dir = OutputDir::create(path, ".fasta", ExistingPolicy::Fail)?;
+            assert!(write(&mut dir, "seq", "second").is_err());
+
+            let mut dir = OutputDir::create(path, ".fasta", ExistingPolicy::Skip)?;
+            assert!(!write(&mut dir, "seq", "second")?);
+            assert_eq!(fs::read_to_string(&file)?, "first");
+
+            let mut dir = OutputDir::create(path, ".fasta", ExistingPolicy::Overwrite)?;
+            assert!(write(&mut dir, "seq", "second")?);
+            assert_eq!(fs::read_to_string(&file)?, "second");
+
+            fs::remove_dir_all(path)?;
+            Ok(())
+        }
+
+        #[test]
+        fn should_not_leave_uncommitted_files() -> Result {
+            let path = "tmp/output_dir_atomic";
+            let _ = fs::remove_dir_all(path);
+            let mut dir = OutputDir::create(path, ".fasta", ExistingPolicy::Fail)?;
+
+            let mut file = dir.open("seq")?.unwrap();
+            write!(file, "partial")?;
+            drop(file);
+
+            assert_eq!(fs::read_dir(path)?.count(), 0);
+
+            fs::remove_dir_all(path)?;
+            Ok(())
+        }
+    }
+
+    mod output_stream {
+        use std::io::Write;
+        use std::path::Path;
+
+        use crate::utils::output::{ExistingPolicy, OutputStream};
+        use crate::Result;
+
+        #[test]
+        #[cfg(unix)]
+        fn should_write_devices_in_place() -> Result {
+            let path = "/dev/null";
+            let mut stream = OutputStream::open(path, ExistingPolicy::Fail)?.unwrap();
+
+            // Checked before committing, so a regression cannot rename a
+            // temporary file over the device.
+            assert!(matches!(stream, OutputStream::Special(_)));
+
+            write!(stream, "discarded")?;
+            stream.commit()?;
+
+            assert!(!Path::new(path).is_file());
+            assert!(!Path::new("/dev/.null.tmp").exists());
+
+            Ok(())
+        }
+    }
+}